@@ -0,0 +1,241 @@
+#![allow(clippy::needless_range_loop)]
+
+// Candidates and solving with the masks of `Bitboard` against the loops they
+// replaced, which are kept here as they were. Run with `cargo bench`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+use sudoku_rust::{
+    bitboard::Bitboard,
+    grid::{peers, Grid},
+    solver,
+};
+
+const PUZZLES: [&str; 4] = [
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79",
+    "000000240500030000000000600000005083020600000001000000300000701060400000000200000",
+    "4.....8.5.3..........7......2.....6.....8.4......1.......6.3.7.5..2.....1.4......",
+    "..53.....8......2..7..1.5..4....53...1..7...6..32...8..6.5....9..4....3......97..",
+];
+
+fn main() {
+    let puzzles: Vec<[[usize; 9]; 9]> = PUZZLES
+        .iter()
+        .map(|puzzle| Grid::parse(puzzle).expect("puzzle").givens())
+        .collect();
+
+    // both ways have to agree before their times mean anything
+    for values in &puzzles {
+        assert_eq!(candidates_loops(values), candidates_bitboard(values));
+        assert_eq!(search_loops(values, 2), solver::search(values, 2));
+    }
+
+    compare(
+        "candidates",
+        &puzzles,
+        candidates_loops,
+        candidates_bitboard,
+    );
+    compare(
+        "solve",
+        &puzzles,
+        |values| search_loops(values, 2),
+        |values| solver::search(values, 2),
+    );
+}
+
+fn compare<T>(
+    name: &str,
+    puzzles: &[[[usize; 9]; 9]],
+    loops: fn(&[[usize; 9]; 9]) -> T,
+    bitboard: fn(&[[usize; 9]; 9]) -> T,
+) {
+    let loops = time(puzzles, loops);
+    let bitboard = time(puzzles, bitboard);
+    println!(
+        "{:<12} loops {:>12.2?}   bitboard {:>12.2?}   {:>6.1}x",
+        name,
+        loops,
+        bitboard,
+        loops.as_secs_f64() / bitboard.as_secs_f64()
+    );
+}
+
+// average time of a pass over all puzzles, passes repeated for a second
+fn time<T>(puzzles: &[[[usize; 9]; 9]], run: fn(&[[usize; 9]; 9]) -> T) -> Duration {
+    let start = Instant::now();
+    let mut passes = 0;
+    while passes == 0 || start.elapsed() < Duration::from_secs(1) {
+        for values in puzzles {
+            black_box(run(black_box(values)));
+        }
+        passes += 1;
+    }
+    start.elapsed() / passes
+}
+
+fn candidates_bitboard(values: &[[usize; 9]; 9]) -> [[u16; 9]; 9] {
+    let board = Bitboard::from_values(values);
+    let mut candidates = [[0; 9]; 9];
+    for y in 0..9 {
+        for x in 0..9 {
+            candidates[y][x] = board.candidates(y, x);
+        }
+    }
+    candidates
+}
+
+// `Board::autofill` before bitboards, with a mask in place of adding notes
+fn candidates_loops(values: &[[usize; 9]; 9]) -> [[u16; 9]; 9] {
+    let mut candidates = [[0; 9]; 9];
+    let mut y: usize = 0;
+    loop {
+        let mut x: usize = 0;
+        loop {
+            if values[y][x] == 0 {
+                let mut value: usize = 1;
+                loop {
+                    let mut possible = true;
+
+                    let mut i: usize = 0;
+
+                    // box
+                    let box_x: usize = x - (x % 3);
+                    let box_y: usize = y - (y % 3);
+
+                    if values[box_y][box_x] == value
+                        || values[box_y][box_x + 1] == value
+                        || values[box_y][box_x + 2] == value
+                        || values[box_y + 1][box_x] == value
+                        || values[box_y + 1][box_x + 1] == value
+                        || values[box_y + 1][box_x + 2] == value
+                        || values[box_y + 2][box_x] == value
+                        || values[box_y + 2][box_x + 1] == value
+                        || values[box_y + 2][box_x + 2] == value
+                    {
+                        possible = false;
+                    }
+                    // line
+                    if possible {
+                        loop {
+                            if values[y][i] == value || values[i][x] == value {
+                                possible = false;
+                                break;
+                            }
+
+                            i += 1;
+                            if i == 9 {
+                                break;
+                            }
+                        }
+                    }
+
+                    if possible {
+                        candidates[y][x] |= 1 << (value - 1);
+                    }
+
+                    value += 1;
+                    if value == 10 {
+                        break;
+                    }
+                }
+            }
+
+            x += 1;
+            if x == 9 {
+                break;
+            }
+        }
+
+        y += 1;
+        if y == 9 {
+            break;
+        }
+    }
+    candidates
+}
+
+// `solver::search` before bitboards
+fn search_loops(values: &[[usize; 9]; 9], limit: usize) -> Vec<[[usize; 9]; 9]> {
+    let mut found = Vec::new();
+    if !is_consistent(values) {
+        return found;
+    }
+
+    let mut values = *values;
+    backtrack(&mut values, limit, &mut found);
+    found
+}
+
+fn is_consistent(values: &[[usize; 9]; 9]) -> bool {
+    for y in 0..9 {
+        for x in 0..9 {
+            let value = values[y][x];
+            if value != 0
+                && peers(y, x)
+                    .into_iter()
+                    .any(|(py, px)| values[py][px] == value)
+            {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn options(values: &[[usize; 9]; 9], y: usize, x: usize) -> [bool; 9] {
+    let mut options = [true; 9];
+    for (py, px) in peers(y, x) {
+        if values[py][px] != 0 {
+            options[values[py][px] - 1] = false;
+        }
+    }
+    options
+}
+
+fn backtrack(values: &mut [[usize; 9]; 9], limit: usize, found: &mut Vec<[[usize; 9]; 9]>) {
+    let mut best: Option<(usize, usize, [bool; 9])> = None;
+    let mut best_count = 10;
+    'cells: for y in 0..9 {
+        for x in 0..9 {
+            if values[y][x] != 0 {
+                continue;
+            }
+
+            let options = options(values, y, x);
+            let count = options.iter().filter(|valid| **valid).count();
+            if count == 0 {
+                return;
+            }
+            if count < best_count {
+                best = Some((y, x, options));
+                best_count = count;
+                if count == 1 {
+                    break 'cells;
+                }
+            }
+        }
+    }
+
+    let (y, x, options) = match best {
+        Some(best) => best,
+        None => {
+            found.push(*values);
+            return;
+        }
+    };
+
+    for value in 1..10 {
+        if !options[value - 1] {
+            continue;
+        }
+        values[y][x] = value;
+        backtrack(values, limit, found);
+        if found.len() >= limit {
+            break;
+        }
+    }
+    values[y][x] = 0;
+}
@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    error::Error,
+    grid::Grid,
+    solver::{self, Solution},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Status {
+    Unique,
+    Multiple,
+    // no solution
+    None,
+    // the line is not a puzzle
+    Invalid,
+}
+
+impl Status {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Status::Unique => "unique",
+            Status::Multiple => "multiple",
+            Status::None => "none",
+            Status::Invalid => "invalid",
+        }
+    }
+}
+
+// What checking one line of a collection found.
+pub struct Entry {
+    // line number in the input, from 1
+    pub line: usize,
+    pub puzzle: String,
+    pub status: Status,
+    // values of the solution, only for `Unique`
+    pub solution: Option<[[usize; 9]; 9]>,
+    // why the line could not be read, only for `Invalid`
+    pub error: Option<String>,
+    // reading and solving the puzzle
+    pub time: Duration,
+}
+
+// Checks every puzzle of a collection, one per line. Empty lines and `#`
+// comments are skipped.
+pub fn check(input: &str) -> Vec<Entry> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                None
+            } else {
+                Some(check_line(index + 1, trimmed))
+            }
+        })
+        .collect()
+}
+
+pub fn check_line(line: usize, puzzle: &str) -> Entry {
+    let start = Instant::now();
+    let mut entry = Entry {
+        line,
+        puzzle: puzzle.to_string(),
+        status: Status::Invalid,
+        solution: None,
+        error: None,
+        time: Duration::ZERO,
+    };
+    match Grid::parse(puzzle) {
+        Ok(grid) => match solver::solve(&grid) {
+            Solution::Unique(solution) => {
+                entry.status = Status::Unique;
+                entry.solution = Some(*solution);
+            }
+            Solution::Multiple => entry.status = Status::Multiple,
+            Solution::None => entry.status = Status::None,
+        },
+        // the puzzle is a single line, only the column tells something
+        Err(Error::Parse {
+            column, message, ..
+        }) => entry.error = Some(format!("column {}: {}", column, message)),
+        Err(error) => entry.error = Some(error.to_string()),
+    }
+    entry.time = start.elapsed();
+    entry
+}
+
+// how many entries have each status and how long they took together
+pub fn summary(entries: &[Entry]) -> String {
+    let total: Duration = entries.iter().map(|entry| entry.time).sum();
+    let counts: Vec<String> = [
+        Status::Unique,
+        Status::Multiple,
+        Status::None,
+        Status::Invalid,
+    ]
+    .into_iter()
+    .map(|status| {
+        let count = entries
+            .iter()
+            .filter(|entry| entry.status == status)
+            .count();
+        format!("{} {}", count, status.name())
+    })
+    .collect();
+    format!(
+        "{} puzzles: {} in {:.3}s",
+        entries.len(),
+        counts.join(", "),
+        total.as_secs_f64()
+    )
+}
+
+// One row per entry under a header, times in milliseconds:
+//
+//     line,puzzle,status,solution,time_ms,error
+//     1,..3.2.6..,unique,483921657...,0.412,
+pub fn csv(entries: &[Entry]) -> String {
+    let mut content = String::from("line,puzzle,status,solution,time_ms,error\n");
+    for entry in entries {
+        content += &format!(
+            "{},{},{},{},{:.3},{}\n",
+            entry.line,
+            csv_field(&entry.puzzle),
+            entry.status.name(),
+            entry.solution.as_ref().map(line).unwrap_or_default(),
+            milliseconds(entry.time),
+            csv_field(entry.error.as_deref().unwrap_or_default()),
+        );
+    }
+    content
+}
+
+// An array with an object per entry, `solution` and `error` are null when
+// they do not apply.
+pub fn json(entries: &[Entry]) -> String {
+    let objects: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "  {{\"line\": {}, \"puzzle\": {}, \"status\": \"{}\", \"solution\": {}, \"time_ms\": {:.3}, \"error\": {}}}",
+                entry.line,
+                json_string(&entry.puzzle),
+                entry.status.name(),
+                entry
+                    .solution
+                    .as_ref()
+                    .map(|solution| json_string(&line(solution)))
+                    .unwrap_or_else(|| String::from("null")),
+                milliseconds(entry.time),
+                entry
+                    .error
+                    .as_deref()
+                    .map(json_string)
+                    .unwrap_or_else(|| String::from("null")),
+            )
+        })
+        .collect();
+    if objects.is_empty() {
+        String::from("[]\n")
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+fn line(values: &[[usize; 9]; 9]) -> String {
+    values
+        .iter()
+        .flatten()
+        .map(|value| value.to_string())
+        .collect()
+}
+
+fn milliseconds(time: Duration) -> f64 {
+    time.as_secs_f64() * 1000.0
+}
+
+// quoted when it holds a comma, quote or line break
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for char in text.chars() {
+        match char {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            char if char.is_control() => quoted += &format!("\\u{:04x}", char as u32),
+            char => quoted.push(char),
+        }
+    }
+    quoted + "\""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{PUZZLE, SOLUTION};
+
+    // entries as checked, with the time left out so the output is fixed
+    fn entries(input: &str) -> Vec<Entry> {
+        let mut entries = check(input);
+        for entry in &mut entries {
+            entry.time = Duration::ZERO;
+        }
+        entries
+    }
+
+    #[test]
+    fn skips_comments() {
+        let input = format!("# collection\n\n{}\n  \n12,\"3\n", PUZZLE);
+        let entries = entries(&input);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].line, 3);
+        assert_eq!(entries[0].status, Status::Unique);
+        assert_eq!(entries[0].solution.as_ref().map(line).unwrap(), SOLUTION);
+        assert_eq!(entries[1].line, 5);
+        assert_eq!(entries[1].status, Status::Invalid);
+        assert!(entries[1].error.as_ref().unwrap().starts_with("column "));
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let content = csv(&entries(&format!("{}\n12,\"3\n", PUZZLE)));
+        let mut rows = content.lines();
+        assert_eq!(
+            rows.next(),
+            Some("line,puzzle,status,solution,time_ms,error")
+        );
+        assert_eq!(
+            rows.next().unwrap(),
+            format!("1,{},unique,{},0.000,", PUZZLE, SOLUTION)
+        );
+        assert!(rows
+            .next()
+            .unwrap()
+            .starts_with("2,\"12,\"\"3\",invalid,,0.000,"));
+        assert_eq!(rows.next(), None);
+
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn json_escaping() {
+        assert_eq!(json(&[]), "[]\n");
+        assert_eq!(
+            json_string("say \"hi\"\\\n\t\u{1}"),
+            "\"say \\\"hi\\\"\\\\\\n\\t\\u0001\""
+        );
+
+        let content = json(&entries(&format!("{}\n", PUZZLE)));
+        assert_eq!(
+            content,
+            format!(
+                "[\n  {{\"line\": 1, \"puzzle\": \"{}\", \"status\": \"unique\", \"solution\": \"{}\", \"time_ms\": 0.000, \"error\": null}}\n]\n",
+                PUZZLE, SOLUTION
+            )
+        );
+        let content = json(&entries("x"));
+        assert!(content.contains("\"solution\": null"));
+        assert!(!content.contains("\"error\": null"));
+    }
+}
@@ -0,0 +1,257 @@
+use crate::grid::Grid;
+
+// Masks hold a bit per value, bit `value - 1` standing for the value.
+pub const ALL: u16 = 0b1_1111_1111;
+
+pub fn mask(value: usize) -> u16 {
+    1 << (value - 1)
+}
+
+// values in a mask, smallest first
+pub fn values(mask: u16) -> impl Iterator<Item = usize> {
+    (1..10).filter(move |value| mask & (1 << (value - 1)) != 0)
+}
+
+fn box_of(y: usize, x: usize) -> usize {
+    y / 3 * 3 + x / 3
+}
+
+// the `i`th cell of box `b`, row by row
+fn box_cell(b: usize, i: usize) -> (usize, usize) {
+    (b / 3 * 3 + i / 3, b % 3 * 3 + i % 3)
+}
+
+// Values of the grid with the candidates of every cell and the values placed
+// in every row, column and box as masks. Placing a value only touches the
+// cells sharing a house with it, so the solver and generator never rescan
+// the grid. Cells are stored row by row.
+#[derive(Clone, Copy)]
+pub struct Bitboard {
+    values: [u8; 81],
+    // values that fit each empty cell, nothing for filled ones
+    candidates: [u16; 81],
+    rows: [u16; 9],
+    columns: [u16; 9],
+    boxes: [u16; 9],
+}
+
+impl Default for Bitboard {
+    fn default() -> Self {
+        Self {
+            values: [0; 81],
+            candidates: [ALL; 81],
+            rows: [0; 9],
+            columns: [0; 9],
+            boxes: [0; 9],
+        }
+    }
+}
+
+impl Bitboard {
+    // Values repeating in a house are allowed, see `is_consistent`.
+    pub fn from_values(values: &[[usize; 9]; 9]) -> Self {
+        let mut board = Bitboard::default();
+        for y in 0..9 {
+            for x in 0..9 {
+                if values[y][x] != 0 {
+                    board.set_value(y, x, values[y][x]);
+                }
+            }
+        }
+        board
+    }
+
+    // every filled cell of the grid, initial or not
+    pub fn from(grid: &Grid) -> Self {
+        let mut values = [[0; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                values[y][x] = grid.cells[y][x].value;
+            }
+        }
+        Bitboard::from_values(&values)
+    }
+
+    pub fn value(&self, y: usize, x: usize) -> usize {
+        self.values[y * 9 + x] as usize
+    }
+
+    pub fn candidates(&self, y: usize, x: usize) -> u16 {
+        self.candidates[y * 9 + x]
+    }
+
+    // values placed in the row, column or box of the cell
+    pub fn used(&self, y: usize, x: usize) -> u16 {
+        self.rows[y] | self.columns[x] | self.boxes[box_of(y, x)]
+    }
+
+    pub fn fits(&self, y: usize, x: usize, value: usize) -> bool {
+        self.used(y, x) & mask(value) == 0
+    }
+
+    // Places a value and takes it from the candidates of the cells sharing a
+    // row, column or box with the cell.
+    pub fn set_value(&mut self, y: usize, x: usize, value: usize) {
+        if self.value(y, x) != 0 {
+            self.clear(y, x);
+        }
+        let bit = mask(value);
+        let b = box_of(y, x);
+        self.values[y * 9 + x] = value as u8;
+        self.candidates[y * 9 + x] = 0;
+        self.rows[y] |= bit;
+        self.columns[x] |= bit;
+        self.boxes[b] |= bit;
+        for i in 0..9 {
+            let (by, bx) = box_cell(b, i);
+            self.candidates[y * 9 + i] &= !bit;
+            self.candidates[i * 9 + x] &= !bit;
+            self.candidates[by * 9 + bx] &= !bit;
+        }
+    }
+
+    // Empties a cell. The houses of the cell are worked out again, as the
+    // value may repeat in them, and so are the candidates of their cells.
+    pub fn clear(&mut self, y: usize, x: usize) {
+        if self.value(y, x) == 0 {
+            return;
+        }
+        self.values[y * 9 + x] = 0;
+
+        let b = box_of(y, x);
+        self.rows[y] = 0;
+        self.columns[x] = 0;
+        self.boxes[b] = 0;
+        for i in 0..9 {
+            let (by, bx) = box_cell(b, i);
+            self.rows[y] |= self.bit(y, i);
+            self.columns[x] |= self.bit(i, x);
+            self.boxes[b] |= self.bit(by, bx);
+        }
+        for i in 0..9 {
+            for (cy, cx) in [(y, i), (i, x), box_cell(b, i)] {
+                if self.value(cy, cx) == 0 {
+                    self.candidates[cy * 9 + cx] = ALL & !self.used(cy, cx);
+                }
+            }
+        }
+    }
+
+    // mask of the value in a cell, nothing if it is empty
+    fn bit(&self, y: usize, x: usize) -> u16 {
+        match self.value(y, x) {
+            0 => 0,
+            value => mask(value),
+        }
+    }
+
+    // The empty cell with the fewest candidates, the first one in row order
+    // of those. A cell without any ends the search right away.
+    pub fn fewest(&self) -> Option<(usize, usize)> {
+        let mut best = None;
+        let mut best_count = 10;
+        for index in 0..81 {
+            if self.values[index] != 0 {
+                continue;
+            }
+            let count = self.candidates[index].count_ones();
+            if count < best_count {
+                best = Some((index / 9, index % 9));
+                best_count = count;
+                if count <= 1 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    // true if no value repeats in a row, column or box, a house then holds as
+    // many values as its mask has bits
+    pub fn is_consistent(&self) -> bool {
+        (0..9).all(|i| {
+            let (mut row, mut column, mut square) = (0, 0, 0);
+            for j in 0..9 {
+                let (by, bx) = box_cell(i, j);
+                row += (self.value(i, j) != 0) as u32;
+                column += (self.value(j, i) != 0) as u32;
+                square += (self.value(by, bx) != 0) as u32;
+            }
+            row == self.rows[i].count_ones()
+                && column == self.columns[i].count_ones()
+                && square == self.boxes[i].count_ones()
+        })
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.values.iter().all(|value| *value != 0) && self.is_consistent()
+    }
+
+    pub fn values(&self) -> [[usize; 9]; 9] {
+        let mut values = [[0; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                values[y][x] = self.value(y, x);
+            }
+        }
+        values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_same(board: &Bitboard, values: &[[usize; 9]; 9]) {
+        let expected = Bitboard::from_values(values);
+        assert_eq!(board.values, expected.values);
+        assert_eq!(board.candidates, expected.candidates);
+        assert_eq!(board.rows, expected.rows);
+        assert_eq!(board.columns, expected.columns);
+        assert_eq!(board.boxes, expected.boxes);
+    }
+
+    // Values set and cleared in a fixed random order, repeats in a house
+    // included, have to leave the masks `from_values` works out.
+    #[test]
+    fn updates_match_from_values() {
+        let mut board = Bitboard::default();
+        let mut values = [[0; 9]; 9];
+        let mut state: u64 = 7;
+        for _ in 0..5000 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let random = (state >> 33) as usize;
+            let (y, x) = (random % 81 / 9, random % 9);
+            let value = random / 81 % 12;
+            if value > 9 {
+                board.clear(y, x);
+                values[y][x] = 0;
+            } else if value > 0 {
+                board.set_value(y, x, value);
+                values[y][x] = value;
+            }
+            assert_same(&board, &values);
+        }
+    }
+
+    #[test]
+    fn candidates() {
+        let mut board = Bitboard::default();
+        board.set_value(0, 0, 5);
+        board.set_value(4, 4, 3);
+        assert_eq!(board.candidates(0, 0), 0);
+        assert_eq!(board.candidates(0, 8), ALL & !mask(5));
+        assert_eq!(board.candidates(2, 2), ALL & !mask(5));
+        assert_eq!(board.candidates(0, 4), ALL & !mask(5) & !mask(3));
+        assert_eq!(values(board.candidates(8, 8)).count(), 9);
+        assert!(!board.fits(4, 0, 3));
+        assert!(board.fits(8, 8, 5));
+
+        board.clear(0, 0);
+        assert_eq!(board.candidates(0, 8), ALL);
+        assert!(board.is_consistent());
+        assert!(!board.is_solved());
+    }
+}
@@ -1,126 +1,229 @@
-use std::collections::HashSet;
-
-use crate::{cell::Cell, grid::Grid, theme::Theme};
-use tui::{
-    backend::CrosstermBackend,
-    buffer::Buffer,
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, StatefulWidget, Widget},
-    Terminal,
+use crate::{
+    bitboard::{self, Bitboard},
+    grid::{Grid, HOUSES},
+    hint::Hint,
+    history::History,
+    theme::Slot,
 };
+use std::collections::BTreeSet;
 //
+#[derive(Default)]
 pub struct Board {
     pub grid: Grid,
     pub current_position: (usize, usize),
+    // cells edited together with the current one, empty unless extended
+    pub selection: BTreeSet<(usize, usize)>,
+    // refuse values clashing with their row, column or box
+    pub strict: bool,
+    history: History,
+    // values of the grid, updated with every value set
+    values: Bitboard,
 }
 
-impl Default for Board {
-    fn default() -> Self {
+impl Board {
+    pub fn from(grid: Grid) -> Self {
         Self {
-            grid: Grid::default(),
+            values: Bitboard::from(&grid),
+            grid,
             current_position: (0, 0),
+            selection: BTreeSet::new(),
+            strict: false,
+            history: History::default(),
         }
     }
-}
 
-impl Board {
-    pub fn from(grid: Grid) -> Self {
-        Self {
-            grid: grid,
-            current_position: (0, 0),
+    // returns false if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        let undone = self.history.undo(&mut self.grid);
+        self.values = Bitboard::from(&self.grid);
+        undone
+    }
+
+    // returns false if there was nothing to redo
+    pub fn redo(&mut self) -> bool {
+        let redone = self.history.redo(&mut self.grid);
+        self.values = Bitboard::from(&self.grid);
+        redone
+    }
+
+    // keeps the grid from before a change so it can be undone
+    fn commit(&mut self, before: Grid) {
+        if before != self.grid {
+            self.history.record(before);
         }
     }
 
     pub fn move_up(&mut self) {
+        self.selection.clear();
         self.current_position.0 = (9 + self.current_position.0 - 1) % 9;
         // TODO: recolour bg
     }
 
     pub fn move_down(&mut self) {
+        self.selection.clear();
         self.current_position.0 = (self.current_position.0 + 1) % 9;
     }
 
     pub fn move_right(&mut self) {
+        self.selection.clear();
         self.current_position.1 = (self.current_position.1 + 1) % 9;
     }
 
     pub fn move_left(&mut self) {
+        self.selection.clear();
         self.current_position.1 = (9 + self.current_position.1 - 1) % 9;
     }
 
-    pub fn set_value(&mut self, value: usize) {
+    // adds the current cell and the given one to the selection and moves there
+    pub fn extend_selection(&mut self, position: (usize, usize)) {
+        self.selection.insert(self.current_position);
+        self.selection.insert(position);
+        self.current_position = position;
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    // selects every empty cell noting the value, returns how many there are
+    pub fn select_candidate(&mut self, value: usize) -> usize {
+        self.selection.clear();
+        for y in 0..9 {
+            for x in 0..9 {
+                let cell = &self.grid.cells[y][x];
+                if cell.value == 0 && cell.options.values[value - 1].valid {
+                    self.selection.insert((y, x));
+                }
+            }
+        }
+        if let Some(first) = self.selection.iter().next() {
+            self.current_position = *first;
+        }
+        self.selection.len()
+    }
+
+    // cells the editing operations apply to
+    fn targets(&self) -> Vec<(usize, usize)> {
+        if self.selection.is_empty() {
+            vec![self.current_position]
+        } else {
+            self.selection.iter().copied().collect()
+        }
+    }
+
+    // returns the cells where the value was refused in strict mode
+    pub fn set_value(&mut self, value: usize) -> Vec<(usize, usize)> {
+        let before = self.grid.clone();
+        let mut refused = Vec::new();
+        for (y, x) in self.targets() {
+            if self.strict
+                && value != 0
+                && self.values.value(y, x) != value
+                && !self.values.fits(y, x, value)
+            {
+                refused.push((y, x));
+                continue;
+            }
+            self.set_value_at(y, x, value);
+        }
+        self.commit(before);
+        refused
+    }
+
+    // fills the current cell with its value from the solution
+    pub fn reveal(&mut self, solution: &[[usize; 9]; 9]) {
         let (y, x) = self.current_position;
+        let before = self.grid.clone();
+        self.set_value_at(y, x, solution[y][x]);
+        self.commit(before);
+    }
+
+    pub fn fill(&mut self, solution: &[[usize; 9]; 9]) {
+        let before = self.grid.clone();
+        for y in 0..9 {
+            for x in 0..9 {
+                self.set_value_at(y, x, solution[y][x]);
+            }
+        }
+        self.commit(before);
+    }
+
+    // Sets a value and takes it from the notes of the cells sharing a row,
+    // column or box with the cell.
+    fn set_value_at(&mut self, y: usize, x: usize, value: usize) {
         if self.grid.cells[y][x].initial {
-            return ();
+            return;
         };
         self.grid.cells[y][x].set_value(value);
+        if value == 0 {
+            self.values.clear(y, x);
+            return;
+        }
+        self.values.set_value(y, x, value);
 
-        // TODO: check conflict
-
-        let mut i = 0;
-        loop {
-            self.grid.cells[i][self.current_position.1].remove_option(value);
-            self.grid.cells[i][self.current_position.1].options.values[value - 1].reset_colors();
-            self.grid.cells[self.current_position.0][i].remove_option(value);
-            self.grid.cells[self.current_position.0][i].options.values[value - 1].reset_colors();
-
-            i += 1;
-            if i == 9 {
-                break;
+        for house in [y, 9 + x, 18 + (y / 3) * 3 + x / 3] {
+            for (py, px) in HOUSES[house] {
+                let cell = &mut self.grid.cells[py][px];
+                cell.remove_option(value);
+                cell.options.values[value - 1].reset_colors();
             }
         }
-
-        // calculate top left position of box, color from there
-        let box_x: usize = x - (x % 3);
-        let box_y: usize = y - (y % 3);
-
-        self.grid.cells[box_y][box_x].remove_option(value);
-        self.grid.cells[box_y][box_x].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y][box_x + 1].remove_option(value);
-        self.grid.cells[box_y][box_x + 1].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y][box_x + 2].remove_option(value);
-        self.grid.cells[box_y][box_x + 2].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 1][box_x].remove_option(value);
-        self.grid.cells[box_y + 1][box_x].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 1][box_x + 1].remove_option(value);
-        self.grid.cells[box_y + 1][box_x + 1].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 1][box_x + 2].remove_option(value);
-        self.grid.cells[box_y + 1][box_x + 2].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 2][box_x].remove_option(value);
-        self.grid.cells[box_y + 2][box_x].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 2][box_x + 1].remove_option(value);
-        self.grid.cells[box_y + 2][box_x + 1].options.values[value - 1].reset_colors();
-        self.grid.cells[box_y + 2][box_x + 2].remove_option(value);
-        self.grid.cells[box_y + 2][box_x + 2].options.values[value - 1].reset_colors();
     }
 
+    // With several cells selected the note is added to all of them, or
+    // removed from all if every one already has it.
     pub fn toggle_option(&mut self, value: usize) {
-        let (x, y) = self.current_position;
-        if !self.grid.cells[x][y].initial {
-            self.grid.cells[x][y].value = 0;
+        let before = self.grid.clone();
+        let targets: Vec<(usize, usize)> = self
+            .targets()
+            .into_iter()
+            .filter(|(y, x)| !self.grid.cells[*y][*x].initial)
+            .collect();
+        let add = targets.iter().any(|(y, x)| {
+            let cell = &self.grid.cells[*y][*x];
+            cell.value != 0 || !cell.options.values[value - 1].valid
+        });
+
+        for (x, y) in targets {
+            if self.grid.cells[x][y].value == 0
+                && self.grid.cells[x][y].options.values[value - 1].valid == add
+            {
+                continue;
+            }
+            self.set_value_at(x, y, 0);
             self.grid.cells[x][y].toggle_option(value);
             if self.grid.cells[x][y].options.values[value - 1].valid {
-                self.grid.cells[x][y].options.values[value - 1].bg = Theme::default().red;
-                self.grid.cells[x][y].options.values[value - 1].fg = Theme::default().white;
+                self.grid.cells[x][y].options.values[value - 1].bg = Slot::Red;
+                self.grid.cells[x][y].options.values[value - 1].fg = Slot::White;
             } else {
                 self.grid.cells[x][y].options.values[value - 1].reset_colors();
             }
         }
+        self.commit(before);
     }
 
     pub fn reset_colors(&mut self) {
+        let before = self.grid.clone();
         self.grid.reset_markings();
+        self.commit(before);
     }
 
     pub fn highlight_only(&mut self, value: usize) {
+        let before = self.grid.clone();
         self.grid.reset_markings();
-        self.highlight(value);
+        self.highlight_value(value);
+        self.commit(before);
     }
 
     pub fn highlight(&mut self, value: usize) {
+        let before = self.grid.clone();
+        self.highlight_value(value);
+        self.commit(before);
+    }
+
+    fn highlight_value(&mut self, value: usize) {
         if value == 0 {
-            return ();
+            return;
         };
 
         let mut y: usize = 0;
@@ -128,11 +231,11 @@ impl Board {
             let mut x: usize = 0;
             loop {
                 if self.grid.cells[y][x].value == value {
-                    self.grid.cells[y][x].bg = Theme::default().blue;
-                    self.grid.cells[y][x].fg = Theme::default().black;
+                    self.grid.cells[y][x].bg = Slot::Blue;
+                    self.grid.cells[y][x].fg = Slot::Black;
                 } else if self.grid.cells[y][x].options.values[value - 1].valid {
-                    self.grid.cells[y][x].options.values[value - 1].bg = Theme::default().blue;
-                    self.grid.cells[y][x].options.values[value - 1].fg = Theme::default().black;
+                    self.grid.cells[y][x].options.values[value - 1].bg = Slot::Blue;
+                    self.grid.cells[y][x].options.values[value - 1].fg = Slot::Black;
                 }
 
                 x += 1;
@@ -147,404 +250,165 @@ impl Board {
         }
     }
 
-    pub fn mark(&mut self, value: usize, color: Color) {
-        let (x, y) = self.current_position;
-        if self.grid.cells[x][y].value == 0 && self.grid.cells[x][y].options.values[value - 1].valid
-        {
-            self.grid.cells[x][y].options.values[value - 1].bg = color;
-            self.grid.cells[x][y].options.values[value - 1].fg = Theme::default().black;
+    // Colors the hint over the marks and highlights already there, undo takes
+    // it off again.
+    pub fn show_hint(&mut self, hint: &Hint) {
+        let before = self.grid.clone();
+        for (y, x) in &hint.cells {
+            let cell = &mut self.grid.cells[*y][*x];
+            cell.bg = Slot::LightGrey;
+            cell.fg = Slot::Black;
+            for value in &hint.values {
+                if cell.value == 0 && cell.options.values[value - 1].valid {
+                    cell.options.values[value - 1].bg = Slot::Blue;
+                    cell.options.values[value - 1].fg = Slot::Black;
+                }
+            }
         }
-    }
 
-    pub fn autofill(&mut self) {
-        let mut y: usize = 0;
-        loop {
-            let mut x: usize = 0;
-            loop {
-                if self.grid.cells[y][x].value == 0 {
-                    // cell needs to be filled with notes
-
-                    // loop over possible values
-                    let mut value: usize = 1;
-                    loop {
-                        // check if sector contains value
-
-                        let mut possible = true;
-
-                        let mut i: usize = 0;
-
-                        // box
-                        let box_x: usize = x - (x % 3);
-                        let box_y: usize = y - (y % 3);
-
-                        if self.grid.cells[box_y][box_x].value == value
-                            || self.grid.cells[box_y][box_x + 1].value == value
-                            || self.grid.cells[box_y][box_x + 2].value == value
-                            || self.grid.cells[box_y + 1][box_x].value == value
-                            || self.grid.cells[box_y + 1][box_x + 1].value == value
-                            || self.grid.cells[box_y + 1][box_x + 2].value == value
-                            || self.grid.cells[box_y + 2][box_x].value == value
-                            || self.grid.cells[box_y + 2][box_x + 1].value == value
-                            || self.grid.cells[box_y + 2][box_x + 2].value == value
-                        {
-                            possible = false;
-                        }
-                        // line
-                        if possible {
-                            loop {
-                                if self.grid.cells[y][i].value == value
-                                    || self.grid.cells[i][x].value == value
-                                {
-                                    possible = false;
-                                    break;
-                                }
-
-                                i += 1;
-                                if i == 9 {
-                                    break;
-                                }
-                            }
-                        }
-
-                        if possible {
-                            self.grid.cells[y][x].add_option(value);
-                        }
-
-                        value += 1;
-                        if value == 10 {
-                            break;
-                        }
-                    }
-                }
+        if let Some((y, x, value)) = hint.placement {
+            self.grid.cells[y][x].options.values[value - 1].bg = Slot::Green;
+            self.grid.cells[y][x].options.values[value - 1].fg = Slot::Black;
+        }
 
-                x += 1;
-                if x == 9 {
-                    break;
-                }
-            }
+        for (y, x, value) in &hint.eliminations {
+            self.grid.cells[*y][*x].options.values[value - 1].bg = Slot::Red;
+            self.grid.cells[*y][*x].options.values[value - 1].fg = Slot::Black;
+        }
+        self.commit(before);
+    }
 
-            y += 1;
-            if y == 9 {
-                break;
+    pub fn mark(&mut self, value: usize, color: Slot) {
+        let before = self.grid.clone();
+        for (x, y) in self.targets() {
+            if self.grid.cells[x][y].value == 0
+                && self.grid.cells[x][y].options.values[value - 1].valid
+            {
+                self.grid.cells[x][y].options.values[value - 1].bg = color;
+                self.grid.cells[x][y].options.values[value - 1].fg = Slot::Black;
             }
         }
+        self.commit(before);
     }
-}
-
-pub struct BoardWidget {}
 
-impl StatefulWidget for BoardWidget {
-    type State = Board;
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
-        // render top line
+    // paints the whole background of the cells, `None` clears it
+    pub fn paint(&mut self, color: Option<Slot>) {
+        let before = self.grid.clone();
+        for (y, x) in self.targets() {
+            self.grid.cells[y][x].paint = color;
+        }
+        self.commit(before);
+    }
 
-        let mut row: u16 = 0;
-        let mut column: u16 = 0;
+    pub fn clear_paint(&mut self) {
+        let before = self.grid.clone();
+        for y in 0..9 {
+            for x in 0..9 {
+                self.grid.cells[y][x].paint = None;
+            }
+        }
+        self.commit(before);
+    }
 
-        loop {
-            loop {
-                // loop controll
-
-                // println!("areax: {} areay: {} areaw: {} areah: {}, bufx: {} bufy: {} statex: {} statey: {}", area.x, area.y, area.width, area.height, bufx, bufy, statex, statey);
-
-                let cell = state.grid.cell_at(row as usize, column as usize);
-                let value = char::from_u32(cell.value as u32 + 48).unwrap_or('e');
-
-                let mut center_x = column * 8 + 1 + area.x + 3;
-                let mut center_y = row * 4 + 1 + area.y + 1;
-
-                if row > 5 {
-                    center_y += 2;
-                } else if row > 2 {
-                    center_y += 1;
-                };
-
-                if column > 5 {
-                    center_x += 2;
-                } else if column > 2 {
-                    center_x += 1;
-                };
-
-                if cell.value != 0 {
-                    // solved cell
-
-                    if cell.initial {
-                        buf.get_mut(center_x, center_y)
-                            .set_char(value)
-                            .set_bg(cell.bg)
-                            .set_fg(cell.fg)
-                            .set_style(Style::default().add_modifier(Modifier::UNDERLINED));
-                    } else {
-                        buf.get_mut(center_x, center_y)
-                            .set_char(value)
-                            .set_bg(cell.bg)
-                            .set_fg(cell.fg)
-                            .set_style(
-                                Style::default()
-                                    .add_modifier(Modifier::UNDERLINED)
-                                    .add_modifier(Modifier::ITALIC),
-                            );
+    pub fn autofill(&mut self) {
+        let before = self.grid.clone();
+        // values that do not clash with the row, column or box
+        for y in 0..9 {
+            for x in 0..9 {
+                if self.grid.cells[y][x].value == 0 {
+                    for value in bitboard::values(self.values.candidates(y, x)) {
+                        self.grid.cells[y][x].add_option(value);
                     }
-
-                    // surroundings
-                    buf.get_mut(center_x - 3, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 3, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 3, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x - 2, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 2, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 2, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x - 1, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 1, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 1, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x + 1, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 1, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 1, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x + 2, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 2, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 2, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x + 3, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 3, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 3, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                } else {
-                    // cell with options
-                    buf.get_mut(center_x - 3, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 2, center_y - 1)
-                        .set_char(if cell.options.values[0].valid {
-                            '1'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[0].bg)
-                        .set_fg(cell.options.values[0].fg);
-                    buf.get_mut(center_x - 1, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x, center_y - 1)
-                        .set_char(if cell.options.values[1].valid {
-                            '2'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[1].bg)
-                        .set_fg(cell.options.values[1].fg);
-                    buf.get_mut(center_x + 1, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 2, center_y - 1)
-                        .set_char(if cell.options.values[2].valid {
-                            '3'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[2].bg)
-                        .set_fg(cell.options.values[2].fg);
-                    buf.get_mut(center_x + 3, center_y - 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x - 3, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 2, center_y)
-                        .set_char(if cell.options.values[3].valid {
-                            '4'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[3].bg)
-                        .set_fg(cell.options.values[3].fg);
-                    buf.get_mut(center_x - 1, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x, center_y)
-                        .set_char(if cell.options.values[4].valid {
-                            '5'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[4].bg)
-                        .set_fg(cell.options.values[4].fg);
-                    buf.get_mut(center_x + 1, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 2, center_y)
-                        .set_char(if cell.options.values[5].valid {
-                            '6'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[5].bg)
-                        .set_fg(cell.options.values[5].fg);
-                    buf.get_mut(center_x + 3, center_y)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-
-                    buf.get_mut(center_x - 3, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x - 2, center_y + 1)
-                        .set_char(if cell.options.values[6].valid {
-                            '7'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[6].bg)
-                        .set_fg(cell.options.values[6].fg);
-                    buf.get_mut(center_x - 1, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x, center_y + 1)
-                        .set_char(if cell.options.values[7].valid {
-                            '8'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[7].bg)
-                        .set_fg(cell.options.values[7].fg);
-                    buf.get_mut(center_x + 1, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                    buf.get_mut(center_x + 2, center_y + 1)
-                        .set_char(if cell.options.values[8].valid {
-                            '9'
-                        } else {
-                            ' '
-                        })
-                        .set_bg(cell.options.values[8].bg)
-                        .set_fg(cell.options.values[8].fg);
-                    buf.get_mut(center_x + 3, center_y + 1)
-                        .set_bg(cell.bg)
-                        .set_fg(cell.fg);
-                }
-
-                // buf.get_mut(area.x + bufx + 1, area.y + bufy + 1)
-                //     .set_char(value);
-
-                // loop controll
-                column += 1;
-                if column == 9 {
-                    column = 0;
-                    break;
                 }
             }
-            row += 1;
-            if row == 9 {
-                break;
+        }
+        self.commit(before);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PUZZLE;
+
+    // the masks kept along the way have to match the ones worked out afresh
+    fn assert_in_sync(board: &Board) {
+        let fresh = Bitboard::from(&board.grid);
+        assert_eq!(board.values.values(), fresh.values());
+        for y in 0..9 {
+            for x in 0..9 {
+                assert_eq!(board.values.candidates(y, x), fresh.candidates(y, x));
             }
         }
+    }
 
-        // render position
-        if state.current_position.0 != 9 && state.current_position.1 != 9 {
-            let mut center_x: u16 = (state.current_position.1 * 8 + 1 + (area.x as usize) + 3)
-                .try_into()
-                .unwrap();
-            let mut center_y: u16 = (state.current_position.0 * 4 + 1 + (area.y as usize) + 1)
-                .try_into()
-                .unwrap();
-
-            if state.current_position.0 > 5 {
-                center_y += 2;
-            } else if state.current_position.0 > 2 {
-                center_y += 1;
-            };
-
-            if state.current_position.1 > 5 {
-                center_x += 2;
-            } else if state.current_position.1 > 2 {
-                center_x += 1;
-            };
-
-            buf.get_mut(center_x - 3, center_y - 2).set_char('.');
-            buf.get_mut(center_x - 2, center_y - 2).set_char('.');
-            buf.get_mut(center_x - 1, center_y - 2).set_char('.');
-            buf.get_mut(center_x, center_y - 2).set_char('.');
-            buf.get_mut(center_x + 1, center_y - 2).set_char('.');
-            buf.get_mut(center_x + 2, center_y - 2).set_char('.');
-            buf.get_mut(center_x + 3, center_y - 2).set_char('.');
-
-            buf.get_mut(center_x - 4, center_y - 1).set_char('.');
-            buf.get_mut(center_x - 4, center_y).set_char('.');
-            buf.get_mut(center_x - 4, center_y + 1).set_char('.');
-
-            buf.get_mut(center_x + 4, center_y - 1).set_char('.');
-            buf.get_mut(center_x + 4, center_y).set_char('.');
-            buf.get_mut(center_x + 4, center_y + 1).set_char('.');
-
-            buf.get_mut(center_x - 3, center_y + 2).set_char('.');
-            buf.get_mut(center_x - 2, center_y + 2).set_char('.');
-            buf.get_mut(center_x - 1, center_y + 2).set_char('.');
-            buf.get_mut(center_x, center_y + 2).set_char('.');
-            buf.get_mut(center_x + 1, center_y + 2).set_char('.');
-            buf.get_mut(center_x + 2, center_y + 2).set_char('.');
-            buf.get_mut(center_x + 3, center_y + 2).set_char('.');
+    #[test]
+    fn values_follow_edits() {
+        let mut board = Board::from(Grid::parse(PUZZLE).unwrap());
+        board.current_position = (0, 2);
+        board.set_value(4);
+        assert_in_sync(&board);
+
+        // a note replaces the value of a filled cell
+        board.toggle_option(1);
+        assert_in_sync(&board);
+        assert_eq!(board.grid.cells[0][2].value, 0);
+
+        board.current_position = (0, 3);
+        board.set_value(6);
+        board.toggle_option(2);
+        board.toggle_option(2);
+        assert_in_sync(&board);
+
+        while board.undo() {
+            assert_in_sync(&board);
+        }
+        while board.redo() {
+            assert_in_sync(&board);
         }
     }
-}
 
-// pub struct BoardWidget {
-//     buffer: Buffer,
-// }
-
-// impl BoardWidget {
-//     pub fn from(board: Board) -> Self {
-//         Self {
-//             buffer: Buffer::default(),
-//         }
-//     }
-// }
-
-// impl Widget for BoardWidget {
-//     fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
-//         todo!()
-//     }
-// }
+    #[test]
+    fn cleared_values_are_free_again() {
+        let mut board = Board::from(Grid::default());
+        board.strict = true;
+        board.set_value(5);
+        board.toggle_option(3);
+        board.autofill();
+        assert!(board.grid.cells[0][0]
+            .options
+            .values
+            .iter()
+            .all(|option| option.valid));
+        assert!(board.grid.cells[0][8].options.values[4].valid);
+
+        board.current_position = (0, 4);
+        assert!(board.set_value(5).is_empty());
+        assert_eq!(board.grid.cells[0][4].value, 5);
+    }
+
+    #[test]
+    fn strict_refuses_clashes() {
+        let mut board = Board::from(Grid::parse(PUZZLE).unwrap());
+        board.strict = true;
+        board.current_position = (0, 2);
+        assert_eq!(board.set_value(5), [(0, 2)]);
+        assert_eq!(board.grid.cells[0][2].value, 0);
+
+        // the other cells of the selection still take the value
+        board.extend_selection((0, 3));
+        assert_eq!(board.set_value(6), [(0, 2)]);
+        assert_eq!(board.grid.cells[0][2].value, 0);
+        assert_eq!(board.grid.cells[0][3].value, 6);
+
+        // setting a value again is not a clash with itself
+        board.clear_selection();
+        assert!(board.set_value(6).is_empty());
+
+        board.strict = false;
+        board.current_position = (0, 2);
+        assert!(board.set_value(5).is_empty());
+        assert_eq!(board.grid.cells[0][2].value, 5);
+        assert!(board.grid.conflicts()[0][0]);
+    }
+}
@@ -1,15 +1,17 @@
-use tui::style::Color;
-
 use crate::cell_options::CellOptions;
-use crate::theme::Theme;
+use crate::theme::Slot;
+
+#[derive(Clone, PartialEq)]
 pub struct Cell {
     pub initial: bool,
     pub value: usize,
     pub options: CellOptions,
-    pub fg: Color,
-    pub bg: Color,
-    default_bg: Color,
-    default_fg: Color,
+    pub fg: Slot,
+    pub bg: Slot,
+    // background painted over the whole cell, kept when highlights are reset
+    pub paint: Option<Slot>,
+    default_bg: Slot,
+    default_fg: Slot,
 }
 
 impl Default for Cell {
@@ -18,10 +20,11 @@ impl Default for Cell {
             initial: false,
             value: 0,
             options: CellOptions::default(),
-            fg: Theme::default().white,
-            bg: Theme::default().dark_grey,
-            default_fg: Theme::default().white,
-            default_bg: Theme::default().dark_grey,
+            fg: Slot::White,
+            bg: Slot::DarkGrey,
+            paint: None,
+            default_fg: Slot::White,
+            default_bg: Slot::DarkGrey,
         }
     }
 }
@@ -43,6 +46,19 @@ impl Cell {
         self.value = value;
     }
 
+    // true if no highlight changed the colors of the cell itself
+    pub fn has_default_colors(&self) -> bool {
+        self.bg == self.default_bg && self.fg == self.default_fg
+    }
+
+    // background and foreground to draw, a highlight wins over the paint
+    pub fn colors(&self) -> (Slot, Slot) {
+        match self.paint {
+            Some(paint) if self.has_default_colors() => (paint, Slot::Black),
+            _ => (self.bg, self.fg),
+        }
+    }
+
     pub fn reset_bg(&mut self) {
         self.bg = self.default_bg;
     }
@@ -1,28 +1,31 @@
-use tui::style::Color;
-
-use crate::theme::Theme;
+use crate::theme::Slot;
 
+#[derive(Clone, PartialEq)]
 pub struct CellOption {
     pub valid: bool,
-    pub fg: Color,
-    pub bg: Color,
-    default_bg: Color,
-    default_fg: Color,
+    pub fg: Slot,
+    pub bg: Slot,
+    default_bg: Slot,
+    default_fg: Slot,
 }
 
 impl Default for CellOption {
     fn default() -> Self {
         Self {
             valid: false,
-            fg: Theme::default().white,
-            bg: Theme::default().dark_grey,
-            default_fg: Theme::default().white,
-            default_bg: Theme::default().dark_grey,
+            fg: Slot::White,
+            bg: Slot::DarkGrey,
+            default_fg: Slot::White,
+            default_bg: Slot::DarkGrey,
         }
     }
 }
 
 impl CellOption {
+    pub fn has_default_colors(&self) -> bool {
+        self.bg == self.default_bg && self.fg == self.default_fg
+    }
+
     pub fn reset_bg(&mut self) {
         self.bg = self.default_bg;
     }
@@ -36,7 +39,7 @@ impl CellOption {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone, PartialEq)]
 pub struct CellOptions {
     pub values: [CellOption; 9],
 }
@@ -1,8 +0,0 @@
-use crate::{cell::Cell, grid::Grid};
-
-#[derive(Default)]
-pub struct Column {}
-
-impl Column {
-    // fn at
-}
@@ -0,0 +1,60 @@
+use crate::error::Error;
+use std::{env, fs, io, path::PathBuf};
+
+// `$XDG_CONFIG_HOME/sudoku-tui/<file>`, or `~/.config/sudoku-tui/<file>`
+pub fn path(file: &str) -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("sudoku-tui").join(file))
+}
+
+// Reads a config file as a toml table, `None` if there is no such file.
+pub fn read(file: &str) -> Result<Option<(String, toml::value::Table)>, Error> {
+    let path = match path(file) {
+        Some(path) => path.to_string_lossy().to_string(),
+        None => return Ok(None),
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(Error::File(path, error)),
+    };
+    match content.parse::<toml::Value>() {
+        Ok(toml::Value::Table(table)) => Ok(Some((content, table))),
+        Ok(_) => Err(Error::parse(1, 1, String::from("expected a table")).in_file(&path)),
+        Err(error) => {
+            let (line, column) = error.line_col().unwrap_or((0, 0));
+            let message = error.to_string();
+            // the message ends with its own position
+            let message = match message.split_once(" at line ") {
+                Some((message, _)) => message.to_string(),
+                None => message,
+            };
+            Err(Error::parse(line + 1, column + 1, message).in_file(&path))
+        }
+    }
+}
+
+// Error pointing at the first line defining `key`, toml values do not keep
+// their position.
+pub fn error(file: &str, content: &str, key: &str, message: String) -> Error {
+    let (line, column) = content
+        .lines()
+        .enumerate()
+        .find_map(|(index, line)| {
+            let trimmed = line.trim_start();
+            let rest = trimmed.strip_prefix(key)?;
+            if rest.trim_start().starts_with('=') {
+                Some((index + 1, line.len() - trimmed.len() + 1))
+            } else {
+                None
+            }
+        })
+        .unwrap_or((1, 1));
+    let path = path(file)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| file.to_string());
+    Error::parse(line, column, message).in_file(&path)
+}
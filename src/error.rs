@@ -0,0 +1,80 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum Error {
+    // reading or writing a file failed
+    File(String, io::Error),
+    // input could not be understood, line and column count from 1
+    Parse {
+        path: Option<String>,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    // command line arguments are missing or wrong
+    Usage(String),
+    // the puzzle has no solution or more than one
+    Solution(String),
+    // setting up or drawing to the terminal failed
+    Terminal(io::Error),
+}
+
+impl Error {
+    pub fn parse(line: usize, column: usize, message: String) -> Self {
+        Error::Parse {
+            path: None,
+            line,
+            column,
+            message,
+        }
+    }
+
+    // names the file a parse error comes from
+    pub fn in_file(self, file: &str) -> Self {
+        match self {
+            Error::Parse {
+                line,
+                column,
+                message,
+                ..
+            } => Error::Parse {
+                path: Some(file.to_string()),
+                line,
+                column,
+                message,
+            },
+            error => error,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::File(path, error) => write!(f, "{}: {}", path, error),
+            Error::Parse {
+                path: Some(path),
+                line,
+                column,
+                message,
+            } => write!(f, "{}:{}:{}: {}", path, line, column, message),
+            Error::Parse {
+                path: None,
+                line,
+                column,
+                message,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            Error::Usage(usage) => write!(f, "{}", usage),
+            Error::Solution(message) => write!(f, "{}", message),
+            Error::Terminal(error) => write!(f, "terminal: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Terminal(error)
+    }
+}
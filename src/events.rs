@@ -1,8 +1,8 @@
 // source:
 // https://github.com/MitchelPaulin/sudoku-rs/blob/main/src/events.rs
 
-use std::{io, sync::mpsc, thread, time::Duration};
-use termion::{event::Key, input::TermRead};
+use crossterm::event::{self, KeyEventKind};
+use std::{sync::mpsc, thread, time::Duration};
 
 pub const TICK_RATE_MS: u64 = 250;
 
@@ -11,10 +11,11 @@ pub enum Event<I> {
     Tick,
 }
 
-// A small event handler that wrap termion input and tick events. Each event
-// type is handled in its own thread and returned to a common `Receiver`
+// A small event handler that wrap crossterm input (keys with their modifiers, mouse, resize and
+// focus) and tick events. Each event type is handled in its own thread and returned to a common
+// `Receiver`
 pub struct Events {
-    rx: mpsc::Receiver<Event<Key>>,
+    rx: mpsc::Receiver<Event<event::Event>>,
     _input_handle: thread::JoinHandle<()>,
     _tick_handle: thread::JoinHandle<()>,
 }
@@ -44,9 +45,14 @@ impl Events {
             _input_handle: {
                 let tx = tx.clone();
                 thread::spawn(move || {
-                    let stdin = io::stdin();
-                    for key in stdin.keys().flatten() {
-                        if let Err(err) = tx.send(Event::Input(key)) {
+                    while let Ok(input) = event::read() {
+                        // some terminals report releasing keys too
+                        if let event::Event::Key(key) = input {
+                            if key.kind == KeyEventKind::Release {
+                                continue;
+                            }
+                        }
+                        if let Err(err) = tx.send(Event::Input(input)) {
                             eprintln!("{}", err);
                             return;
                         }
@@ -64,7 +70,7 @@ impl Events {
         }
     }
 
-    pub fn next(&self) -> Result<Event<Key>, mpsc::RecvError> {
+    pub fn next(&self) -> Result<Event<event::Event>, mpsc::RecvError> {
         self.rx.recv()
     }
 }
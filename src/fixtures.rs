@@ -0,0 +1,19 @@
+// Puzzles and helpers shared by the tests of several modules.
+
+// the puzzle of the Wikipedia article on sudoku, with a unique solution
+pub const PUZZLE: &str =
+    "53..7....6..195....98....6.8...6...34..8.3..17...2...6.6....28....419..5....8..79";
+pub const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+// values row by row on one line, `.` for empty cells
+pub fn line(values: &[[usize; 9]; 9]) -> String {
+    values
+        .iter()
+        .flatten()
+        .map(|value| match value {
+            0 => '.',
+            value => char::from(b'0' + *value as u8),
+        })
+        .collect()
+}
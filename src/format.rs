@@ -0,0 +1,140 @@
+use crate::{
+    error::Error,
+    grid::Grid,
+    sync::{self, Session},
+};
+
+// Ways of writing a puzzle down. All but `Sudoku` only hold the values of
+// the cells and are read by `Grid::parse`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Format {
+    // 81 cells on one line, `.` for empty ones
+    Line,
+    // nine lines of nine cells
+    Grid,
+    // SadMan Sudoku, the grid under a `[Puzzle]` header
+    Sdk,
+    // SimpleSudoku, the grid with `|` and `-` between boxes
+    Ss,
+    // saved game with notes, colors and the session
+    Sudoku,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::Line,
+        Format::Grid,
+        Format::Sdk,
+        Format::Ss,
+        Format::Sudoku,
+    ];
+
+    pub fn from(name: &str) -> Option<Self> {
+        Format::ALL
+            .into_iter()
+            .find(|format| format.name() == name.to_lowercase())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Format::Line => "line",
+            Format::Grid => "grid",
+            Format::Sdk => "sdk",
+            Format::Ss => "ss",
+            Format::Sudoku => "sudoku",
+        }
+    }
+
+    // format of a file by its extension, `None` for anything unknown
+    pub fn of_path(path: &str) -> Option<Self> {
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension {
+            "sdk" => Some(Format::Sdk),
+            "ss" => Some(Format::Ss),
+            "sudoku" => Some(Format::Sudoku),
+            _ => None,
+        }
+    }
+
+    pub fn read(&self, content: &str) -> Result<(Grid, Session), Error> {
+        match self {
+            Format::Sudoku => sync::parse(content),
+            _ => Ok((Grid::parse(content)?, Session::default())),
+        }
+    }
+
+    // Every filled cell is written, so a puzzle in progress comes out with
+    // the values entered so far.
+    pub fn write(&self, grid: &Grid, session: &Session) -> String {
+        let rows: Vec<String> = grid
+            .cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|cell| match cell.value {
+                        0 => '.',
+                        value => char::from(b'0' + value as u8),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        match self {
+            Format::Line => rows.concat() + "\n",
+            Format::Grid => rows.join("\n") + "\n",
+            Format::Sdk => format!("[Puzzle]\n{}\n", rows.join("\n")),
+            Format::Ss => {
+                let mut content = String::new();
+                for (y, row) in rows.iter().enumerate() {
+                    if y == 3 || y == 6 {
+                        content += "-----------\n";
+                    }
+                    content += &format!("{}|{}|{}\n", &row[0..3], &row[3..6], &row[6..9]);
+                }
+                content
+            }
+            Format::Sudoku => sync::to_string(grid, session),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PUZZLE;
+
+    fn values(grid: &Grid) -> Vec<usize> {
+        grid.cells.iter().flatten().map(|cell| cell.value).collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        // a puzzle in progress, with one value entered
+        let mut grid = Grid::parse(PUZZLE).unwrap();
+        grid.cells[0][2].value = 4;
+        let session = Session {
+            time: 90,
+            ..Session::default()
+        };
+
+        for format in Format::ALL {
+            let (read, restored) = format.read(&format.write(&grid, &session)).unwrap();
+            assert_eq!(values(&read), values(&grid), "{}", format.name());
+            assert_eq!(Format::from(format.name()), Some(format));
+            if format == Format::Sudoku {
+                assert!(read == grid);
+                assert_eq!(restored.time, 90);
+            }
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Format::from("SDK"), Some(Format::Sdk));
+        assert_eq!(Format::from("csv"), None);
+        assert_eq!(Format::of_path("puzzles/one.ss"), Some(Format::Ss));
+        assert_eq!(Format::of_path("game.sudoku"), Some(Format::Sudoku));
+        assert_eq!(Format::of_path("puzzle.txt"), None);
+        assert_eq!(Format::of_path("puzzle"), None);
+    }
+}
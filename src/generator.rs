@@ -0,0 +1,228 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{grid::Grid, hint::Technique, rater, solver};
+
+// attempts at hitting the requested difficulty before settling for the closest one
+const ATTEMPTS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn from(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" => Some(Difficulty::Easy),
+            "medium" => Some(Difficulty::Medium),
+            "hard" => Some(Difficulty::Hard),
+            "expert" => Some(Difficulty::Expert),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        }
+    }
+
+    // fewest initial cells a puzzle of this difficulty keeps
+    fn clues(&self) -> usize {
+        match self {
+            Difficulty::Easy => 36,
+            Difficulty::Medium => 30,
+            Difficulty::Hard => 24,
+            Difficulty::Expert => 17,
+        }
+    }
+
+    // Difficulty of a puzzle from the hardest technique it needs, None meaning
+    // it can not be solved with hints alone.
+    pub fn of(hardest: Option<Technique>) -> Self {
+        match hardest {
+            Some(Technique::NakedSingle | Technique::HiddenSingle) => Difficulty::Easy,
+            Some(Technique::PointingPair | Technique::BoxLineReduction) => Difficulty::Medium,
+            Some(_) => Difficulty::Hard,
+            None => Difficulty::Expert,
+        }
+    }
+}
+
+// Generates a puzzle with a unique solution along with the difficulty it
+// rates at, which is the requested one unless every attempt missed it. The
+// same difficulty and seed always give the same puzzle.
+pub fn generate(difficulty: Difficulty, seed: u64) -> (Grid, Difficulty) {
+    let mut random = Random::new(seed);
+    let mut best: Option<(Difficulty, [[usize; 9]; 9])> = None;
+    for _ in 0..ATTEMPTS {
+        let puzzle = dig(&solution(&mut random), difficulty, &mut random);
+        let reached = rater::rate(&Grid::from_values(&puzzle)).difficulty;
+        if reached == difficulty {
+            return (Grid::from_values(&puzzle), reached);
+        }
+        if best.is_none_or(|(closest, _)| reached > closest) {
+            best = Some((reached, puzzle));
+        }
+    }
+    match best {
+        Some((reached, puzzle)) => (Grid::from_values(&puzzle), reached),
+        None => (Grid::default(), difficulty),
+    }
+}
+
+// what was generated, and what was asked for when it was missed
+pub fn describe(requested: Difficulty, reached: Difficulty, seed: u64) -> String {
+    if requested == reached {
+        format!("{} puzzle, seed {}", reached.name(), seed)
+    } else {
+        format!(
+            "{} puzzle, seed {}, no {} one found",
+            reached.name(),
+            seed,
+            requested.name()
+        )
+    }
+}
+
+// seed for when none was asked for
+pub fn random_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// A random filled grid: the diagonal boxes do not constrain each other, so they
+// are shuffled freely and the solver completes the rest.
+fn solution(random: &mut Random) -> [[usize; 9]; 9] {
+    let mut values = [[0; 9]; 9];
+    for b in 0..3 {
+        let mut digits = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        random.shuffle(&mut digits);
+        for i in 0..9 {
+            values[b * 3 + i / 3][b * 3 + i % 3] = digits[i];
+        }
+    }
+    solver::search(&values, 1)[0]
+}
+
+// Removes values in symmetric pairs for as long as the solution stays unique
+// and the puzzle does not get harder than requested.
+fn dig(solution: &[[usize; 9]; 9], difficulty: Difficulty, random: &mut Random) -> [[usize; 9]; 9] {
+    let mut puzzle = *solution;
+    let mut clues = 81;
+    let mut positions: Vec<usize> = (0..41).collect();
+    random.shuffle(&mut positions);
+
+    for position in positions {
+        let (y, x) = (position / 9, position % 9);
+        let removed = if position == 40 { 1 } else { 2 };
+        if clues - removed < difficulty.clues() {
+            continue;
+        }
+        let (my, mx) = (8 - y, 8 - x);
+
+        let mut attempt = puzzle;
+        attempt[y][x] = 0;
+        attempt[my][mx] = 0;
+
+        if solver::search(&attempt, 2).len() != 1 {
+            continue;
+        }
+        if difficulty != Difficulty::Expert
+            && rater::rate(&Grid::from_values(&attempt)).difficulty > difficulty
+        {
+            continue;
+        }
+        puzzle = attempt;
+        clues -= removed;
+    }
+    puzzle
+}
+
+// SplitMix64, small and reproducible for a given seed.
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::line, solver::Solution};
+
+    #[test]
+    fn reproducible() {
+        let (grid, reached) = generate(Difficulty::Easy, 1);
+        assert_eq!(reached, Difficulty::Easy);
+        assert_eq!(
+            line(&grid.givens()),
+            ".54...192.....3846..6....5..3794.21....3.5....98.7146..6....5..7134.....945...72."
+        );
+        assert!(generate(Difficulty::Easy, 1).0 == grid);
+        assert!(generate(Difficulty::Easy, 2).0 != grid);
+    }
+
+    #[test]
+    fn unique_and_rated() {
+        for difficulty in [Difficulty::Easy, Difficulty::Medium] {
+            let (grid, reached) = generate(difficulty, 1);
+            assert_eq!(reached, difficulty);
+            assert!(matches!(solver::solve(&grid), Solution::Unique(_)));
+            assert_eq!(rater::rate(&grid).difficulty, reached);
+            let clues = grid.givens().iter().flatten().filter(|v| **v != 0).count();
+            assert!(clues >= difficulty.clues());
+        }
+    }
+
+    #[test]
+    fn names() {
+        for difficulty in Difficulty::ALL {
+            assert_eq!(Difficulty::from(difficulty.name()), Some(difficulty));
+        }
+        assert_eq!(Difficulty::from("Hard"), Some(Difficulty::Hard));
+        assert_eq!(Difficulty::from("insane"), None);
+        assert_eq!(
+            describe(Difficulty::Expert, Difficulty::Hard, 7),
+            "hard puzzle, seed 7, no expert one found"
+        );
+    }
+}
@@ -1,26 +1,81 @@
-use crate::cell::Cell;
-use tui::{
-    backend::CrosstermBackend,
-    layout::{Constraint, Direction, Layout},
-    widgets::{Block, Borders, Widget},
-    Terminal,
-};
-
-#[derive(Default)]
+use crate::{cell::Cell, error::Error};
+
+#[derive(Default, Clone, PartialEq)]
 pub struct Grid {
     // outer array is of rows, inner are cells
     pub cells: [[Cell; 9]; 9],
 }
 
 impl Grid {
-    pub fn from(input: String) -> Self {
+    // Reads 81 cells row by row. Empty cells are `0`, `.` or `_`; spaces, line
+    // breaks and the `|`, `-`, `+`, `*` separators and borders of 9x9 layouts
+    // are skipped. This covers single line puzzles as well as SadMan (`.sdk`,
+    // `#` comment lines and an optional `[Puzzle]` section) and SimpleSudoku
+    // (`.ss`, with or without the `*-----------*` border) files.
+    pub fn parse(input: &str) -> Result<Self, Error> {
+        let mut grid = Grid::default();
+        let mut count = 0;
+        let mut line_count = 0;
+        for (line_index, line) in input.lines().enumerate() {
+            line_count = line_index + 1;
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') || trimmed == "[Puzzle]" {
+                continue;
+            }
+            // any other section, like the candidates of a SadMan file
+            if trimmed.starts_with('[') {
+                break;
+            }
+
+            for (index, char) in line.chars().enumerate() {
+                let value = match char {
+                    '1'..='9' => char.to_digit(10).unwrap_or(0) as usize,
+                    '0' | '.' | '_' => 0,
+                    '|' | '-' | '+' | '*' => continue,
+                    _ if char.is_whitespace() => continue,
+                    _ => {
+                        return Err(Error::parse(
+                            line_index + 1,
+                            index + 1,
+                            format!("unexpected character '{}'", char),
+                        ))
+                    }
+                };
+                if count == 81 {
+                    return Err(Error::parse(
+                        line_index + 1,
+                        index + 1,
+                        String::from("expected 81 cells, found more"),
+                    ));
+                }
+                if value != 0 {
+                    let column = count % 9;
+                    let row = (count - column) / 9;
+                    grid.cells[row][column].initial = true;
+                    grid.cells[row][column].value = value;
+                }
+                count += 1;
+            }
+        }
+        if count != 81 {
+            return Err(Error::parse(
+                line_count.max(1),
+                1,
+                format!("expected 81 cells, found {}", count),
+            ));
+        }
+        Ok(grid)
+    }
+
+    // non-zero values become initial cells
+    pub fn from_values(values: &[[usize; 9]; 9]) -> Self {
         let mut grid = Grid::default();
-        for (index, char) in input.chars().enumerate() {
-            if char != '0' {
-                let column = index % 9;
-                let row = (index - column) / 9;
-                grid.cells[row][column].initial = true;
-                grid.cells[row][column].value = char.to_digit(10).unwrap_or(0) as usize;
+        for y in 0..9 {
+            for x in 0..9 {
+                if values[y][x] != 0 {
+                    grid.cells[y][x].initial = true;
+                    grid.cells[y][x].value = values[y][x];
+                }
             }
         }
         grid
@@ -30,6 +85,45 @@ impl Grid {
         &self.cells[row][column]
     }
 
+    // cells sharing a row, column or box with the given one that hold the value
+    pub fn clashes(&self, y: usize, x: usize, value: usize) -> Vec<(usize, usize)> {
+        peers(y, x)
+            .into_iter()
+            .filter(|(py, px)| self.cells[*py][*px].value == value)
+            .collect()
+    }
+
+    // filled cells whose value repeats in their row, column or box
+    pub fn conflicts(&self) -> [[bool; 9]; 9] {
+        let mut conflicts = [[false; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                let value = self.cells[y][x].value;
+                conflicts[y][x] = value != 0 && !self.clashes(y, x, value).is_empty();
+            }
+        }
+        conflicts
+    }
+
+    // every cell filled and no value repeating
+    pub fn is_solved(&self) -> bool {
+        let conflicts = self.conflicts();
+        (0..9).all(|y| (0..9).all(|x| self.cells[y][x].value != 0 && !conflicts[y][x]))
+    }
+
+    // values of the initial cells only
+    pub fn givens(&self) -> [[usize; 9]; 9] {
+        let mut values = [[0; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                if self.cells[y][x].initial {
+                    values[y][x] = self.cells[y][x].value;
+                }
+            }
+        }
+        values
+    }
+
     pub fn reset_markings(&mut self) {
         // for (_, mut row) in self.cells.into_iter().enumerate() {
         //     for (_, mut cell) in row.into_iter().enumerate() {
@@ -56,3 +150,182 @@ impl Grid {
         }
     }
 }
+
+// Rows, columns and boxes, in that order.
+pub const HOUSES: [[(usize, usize); 9]; 27] = houses();
+
+const fn houses() -> [[(usize, usize); 9]; 27] {
+    let mut houses = [[(0, 0); 9]; 27];
+    let mut i = 0;
+    while i < 9 {
+        let mut j = 0;
+        while j < 9 {
+            houses[i][j] = (i, j);
+            houses[9 + i][j] = (j, i);
+            houses[18 + i][j] = ((i / 3) * 3 + j / 3, (i % 3) * 3 + j % 3);
+            j += 1;
+        }
+        i += 1;
+    }
+    houses
+}
+
+// Every cell sharing a row, column or box with the given one.
+pub fn peers(y: usize, x: usize) -> Vec<(usize, usize)> {
+    let box_y = y - (y % 3);
+    let box_x = x - (x % 3);
+
+    let mut peers = Vec::new();
+    for i in 0..9 {
+        if i != x {
+            peers.push((y, i));
+        }
+        if i != y {
+            peers.push((i, x));
+        }
+        let (py, px) = (box_y + i / 3, box_x + i % 3);
+        if py != y && px != x {
+            peers.push((py, px));
+        }
+    }
+    peers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{line, PUZZLE};
+
+    fn parse_error(input: &str) -> (usize, usize, String) {
+        match Grid::parse(input) {
+            Err(Error::Parse {
+                line,
+                column,
+                message,
+                ..
+            }) => (line, column, message),
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn single_line() {
+        let grid = Grid::parse(PUZZLE).unwrap();
+        assert_eq!(line(&grid.givens()), PUZZLE);
+        assert_eq!(grid.cells[0][0].value, 5);
+        assert!(grid.cells[0][0].initial);
+        assert_eq!(grid.cells[8][8].value, 9);
+
+        // `0` and `_` are empty cells as well
+        let other = PUZZLE.replacen('.', "0", 5).replacen('.', "_", 5);
+        assert_eq!(line(&Grid::parse(&other).unwrap().givens()), PUZZLE);
+    }
+
+    #[test]
+    fn nine_lines() {
+        let grid = "\
+5 3 . | . 7 . | . . .
+6 . . | 1 9 5 | . . .
+. 9 8 | . . . | . 6 .
+------+-------+------
+8 . . | . 6 . | . . 3
+4 . . | 8 . 3 | . . 1
+7 . . | . 2 . | . . 6
+------+-------+------
+. 6 . | . . . | 2 8 .
+. . . | 4 1 9 | . . 5
+. . . | . 8 . | . 7 9
+";
+        assert_eq!(line(&Grid::parse(grid).unwrap().givens()), PUZZLE);
+    }
+
+    #[test]
+    fn errors() {
+        let (line, column, message) = parse_error("53..7....\n6..19x...");
+        assert_eq!((line, column), (2, 6));
+        assert_eq!(message, "unexpected character 'x'");
+
+        let (line, _, message) = parse_error(&PUZZLE[..80]);
+        assert_eq!(line, 1);
+        assert_eq!(message, "expected 81 cells, found 80");
+
+        let (line, column, message) = parse_error(&format!("{}\n1", PUZZLE));
+        assert_eq!((line, column), (2, 1));
+        assert_eq!(message, "expected 81 cells, found more");
+
+        assert_eq!(parse_error("").2, "expected 81 cells, found 0");
+    }
+
+    #[test]
+    fn simple_sudoku() {
+        let plain = "\
+53.|.7.|...
+6..|195|...
+.98|...|.6.
+-----------
+8..|.6.|..3
+4..|8.3|..1
+7..|.2.|..6
+-----------
+.6.|...|28.
+...|419|..5
+...|.8.|.79
+";
+        let bordered = "\
+*-----------*
+|53.|.7.|...|
+|6..|195|...|
+|.98|...|.6.|
+|---+---+---|
+|8..|.6.|..3|
+|4..|8.3|..1|
+|7..|.2.|..6|
+|---+---+---|
+|.6.|...|28.|
+|...|419|..5|
+|...|.8.|.79|
+*-----------*
+";
+        assert_eq!(line(&Grid::parse(plain).unwrap().givens()), PUZZLE);
+        assert_eq!(line(&Grid::parse(bordered).unwrap().givens()), PUZZLE);
+    }
+
+    #[test]
+    fn sadman() {
+        let sdk = "\
+#A Tester
+#D Taken from the classic example
+#L Easy
+[Puzzle]
+53..7....
+6..195...
+.98....6.
+8...6...3
+4..8.3..1
+7...2...6
+.6....28.
+...419..5
+....8..79
+[State]
+534678912
+";
+        let grid = Grid::parse(sdk).unwrap();
+        assert_eq!(line(&grid.givens()), PUZZLE);
+        assert!(grid.cells[0][0].initial);
+        assert!(!grid.cells[0][2].initial);
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut grid = Grid::parse(PUZZLE).unwrap();
+        assert!(grid.conflicts().iter().flatten().all(|conflict| !conflict));
+
+        // a second 5 in the first row clashes with the given one only
+        grid.cells[0][2].value = 5;
+        let conflicts = grid.conflicts();
+        assert!(conflicts[0][0] && conflicts[0][2]);
+        assert_eq!(conflicts.iter().flatten().filter(|c| **c).count(), 2);
+        assert_eq!(grid.clashes(0, 2, 5), [(0, 0)]);
+        assert!(!grid.is_solved());
+    }
+}
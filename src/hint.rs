@@ -0,0 +1,555 @@
+use crate::{
+    bitboard::{self, Bitboard},
+    grid::{Grid, HOUSES},
+};
+
+// Deductions the hint engine knows about, ordered from the simplest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Technique {
+    HiddenSingle,
+    NakedSingle,
+    PointingPair,
+    BoxLineReduction,
+    NakedPair,
+    XWing,
+    HiddenPair,
+}
+
+impl Technique {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Technique::NakedSingle => "naked single",
+            Technique::HiddenSingle => "hidden single",
+            Technique::PointingPair => "pointing pair",
+            Technique::BoxLineReduction => "box/line reduction",
+            Technique::NakedPair => "naked pair",
+            Technique::HiddenPair => "hidden pair",
+            Technique::XWing => "x-wing",
+        }
+    }
+
+    // Sudoku Explainer rating of the technique
+    pub fn score(&self) -> f32 {
+        match self {
+            Technique::HiddenSingle => 1.5,
+            Technique::NakedSingle => 2.3,
+            Technique::PointingPair => 2.6,
+            Technique::BoxLineReduction => 2.8,
+            Technique::NakedPair => 3.0,
+            Technique::XWing => 3.2,
+            Technique::HiddenPair => 3.4,
+        }
+    }
+}
+
+pub struct Hint {
+    pub technique: Technique,
+    // cells forming the pattern, (row, column)
+    pub cells: Vec<(usize, usize)>,
+    // digits the pattern is built from
+    pub values: Vec<usize>,
+    // value that can be placed, (row, column, value)
+    pub placement: Option<(usize, usize, usize)>,
+    // candidates removed by the deduction, (row, column, value)
+    pub eliminations: Vec<(usize, usize, usize)>,
+    description: String,
+}
+
+impl Hint {
+    pub fn describe(&self) -> String {
+        format!("{}: {}", self.technique.name(), self.description)
+    }
+}
+
+// Values and candidates of every cell, detached from colors so deductions can
+// be applied without touching the board. Candidates are masks as in
+// `bitboard`, a bit per value.
+#[derive(Clone)]
+pub struct Candidates {
+    board: Bitboard,
+    options: [[u16; 9]; 9],
+}
+
+impl Candidates {
+    // Notes written in the grid are used as candidates, limited to values that
+    // do not clash with the row, column or box. Cells without any notes get
+    // every value that does not clash.
+    pub fn from(grid: &Grid) -> Self {
+        let board = Bitboard::from(grid);
+        let mut options = [[0; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                let notes = grid.cells[y][x]
+                    .options
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, option)| option.valid)
+                    .fold(0, |notes, (index, _)| notes | bitboard::mask(index + 1));
+                options[y][x] =
+                    board.candidates(y, x) & if notes == 0 { bitboard::ALL } else { notes };
+            }
+        }
+        Candidates { board, options }
+    }
+
+    // true if no peer of the cell holds the value
+    pub fn fits(&self, y: usize, x: usize, value: usize) -> bool {
+        self.board.fits(y, x, value)
+    }
+
+    pub fn count(&self, y: usize, x: usize) -> usize {
+        self.options[y][x].count_ones() as usize
+    }
+
+    pub fn is_solved(&self) -> bool {
+        (0..9).all(|y| (0..9).all(|x| self.board.value(y, x) != 0))
+    }
+
+    pub fn apply(&mut self, hint: &Hint) {
+        if let Some((y, x, value)) = hint.placement {
+            self.board.set_value(y, x, value);
+            self.options[y][x] = 0;
+            for house in [y, 9 + x, 18 + (y / 3) * 3 + x / 3] {
+                for (py, px) in HOUSES[house] {
+                    self.options[py][px] &= !bitboard::mask(value);
+                }
+            }
+        }
+
+        for (y, x, value) in &hint.eliminations {
+            self.options[*y][*x] &= !bitboard::mask(*value);
+        }
+    }
+
+    fn has(&self, (y, x): (usize, usize), value: usize) -> bool {
+        self.options[y][x] & bitboard::mask(value) != 0
+    }
+
+    // cells of the house the value fits, bit `i` for the `i`th cell
+    fn places(&self, house: &[(usize, usize); 9], value: usize) -> u16 {
+        (0..9)
+            .filter(|i| self.has(house[*i], value))
+            .fold(0, |places, i| places | 1 << i)
+    }
+}
+
+// Finds the simplest deduction available on the grid.
+pub fn find(grid: &Grid) -> Option<Hint> {
+    next(&Candidates::from(grid))
+}
+
+pub fn next(candidates: &Candidates) -> Option<Hint> {
+    hidden_single(candidates)
+        .or_else(|| naked_single(candidates))
+        .or_else(|| pointing_pair(candidates))
+        .or_else(|| box_line_reduction(candidates))
+        .or_else(|| naked_pair(candidates))
+        .or_else(|| x_wing(candidates))
+        .or_else(|| hidden_pair(candidates))
+}
+
+pub fn house_name(index: usize) -> String {
+    match index {
+        0..=8 => format!("row {}", index + 1),
+        9..=17 => format!("column {}", index - 8),
+        _ => format!("box {}", index - 17),
+    }
+}
+
+pub fn position(cell: (usize, usize)) -> String {
+    format!("r{}c{}", cell.0 + 1, cell.1 + 1)
+}
+
+fn positions(cells: &[(usize, usize)]) -> String {
+    cells
+        .iter()
+        .map(|cell| position(*cell))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn eliminated_cells(eliminations: &[(usize, usize, usize)]) -> Vec<(usize, usize)> {
+    let mut cells: Vec<(usize, usize)> = Vec::new();
+    for (y, x, _) in eliminations {
+        if !cells.contains(&(*y, *x)) {
+            cells.push((*y, *x));
+        }
+    }
+    cells
+}
+
+fn box_of(cell: (usize, usize)) -> usize {
+    (cell.0 / 3) * 3 + cell.1 / 3
+}
+
+// indices of the bits set in a mask of house cells, smallest first
+fn indices(places: u16) -> impl Iterator<Item = usize> {
+    (0..9).filter(move |i| places & (1 << i) != 0)
+}
+
+fn cells(house: &[(usize, usize); 9], places: u16) -> Vec<(usize, usize)> {
+    indices(places).map(|i| house[i]).collect()
+}
+
+fn naked_single(candidates: &Candidates) -> Option<Hint> {
+    for y in 0..9 {
+        for x in 0..9 {
+            if candidates.board.value(y, x) != 0 || candidates.count(y, x) != 1 {
+                continue;
+            }
+            let value = candidates.options[y][x].trailing_zeros() as usize + 1;
+            return Some(Hint {
+                technique: Technique::NakedSingle,
+                cells: vec![(y, x)],
+                values: vec![value],
+                placement: Some((y, x, value)),
+                eliminations: Vec::new(),
+                description: format!("{} can only be {}", position((y, x)), value),
+            });
+        }
+    }
+    None
+}
+
+fn hidden_single(candidates: &Candidates) -> Option<Hint> {
+    for (index, house) in HOUSES.iter().enumerate() {
+        for value in 1..10 {
+            let places = candidates.places(house, value);
+            if places.count_ones() != 1 {
+                continue;
+            }
+            let (y, x) = house[places.trailing_zeros() as usize];
+            return Some(Hint {
+                technique: Technique::HiddenSingle,
+                cells: house.to_vec(),
+                values: vec![value],
+                placement: Some((y, x, value)),
+                eliminations: Vec::new(),
+                description: format!(
+                    "{} fits only {} in {}",
+                    value,
+                    position((y, x)),
+                    house_name(index)
+                ),
+            });
+        }
+    }
+    None
+}
+
+// Candidates of a value inside a box confined to one row or column.
+fn pointing_pair(candidates: &Candidates) -> Option<Hint> {
+    for b in 18..27 {
+        for value in 1..10 {
+            let places = candidates.places(&HOUSES[b], value);
+            if places.count_ones() < 2 {
+                continue;
+            }
+
+            // box cells go row by row, three to a row
+            let (first_y, first_x) = HOUSES[b][places.trailing_zeros() as usize];
+            let line = if (0..3).any(|row| places & !(0b111 << (row * 3)) == 0) {
+                first_y
+            } else if (0..3).any(|column| places & !(0b001_001_001 << column) == 0) {
+                9 + first_x
+            } else {
+                continue;
+            };
+
+            let eliminations: Vec<(usize, usize, usize)> = HOUSES[line]
+                .iter()
+                .filter(|cell| box_of(**cell) != b - 18 && candidates.has(**cell, value))
+                .map(|(y, x)| (*y, *x, value))
+                .collect();
+            if eliminations.is_empty() {
+                continue;
+            }
+
+            let description = format!(
+                "{} in {} lies only in {}, remove it from {}",
+                value,
+                house_name(b),
+                house_name(line),
+                positions(&eliminated_cells(&eliminations))
+            );
+            return Some(Hint {
+                technique: Technique::PointingPair,
+                cells: cells(&HOUSES[b], places),
+                values: vec![value],
+                placement: None,
+                eliminations,
+                description,
+            });
+        }
+    }
+    None
+}
+
+// Candidates of a value inside a row or column confined to one box.
+fn box_line_reduction(candidates: &Candidates) -> Option<Hint> {
+    for line in 0..18 {
+        for value in 1..10 {
+            let places = candidates.places(&HOUSES[line], value);
+            // a row or column crosses three boxes, three cells in each
+            if places.count_ones() < 2 || !(0..3).any(|b| places & !(0b111 << (b * 3)) == 0) {
+                continue;
+            }
+
+            let b = 18 + box_of(HOUSES[line][places.trailing_zeros() as usize]);
+            let eliminations: Vec<(usize, usize, usize)> = HOUSES[b]
+                .iter()
+                .filter(|cell| !HOUSES[line].contains(cell) && candidates.has(**cell, value))
+                .map(|(y, x)| (*y, *x, value))
+                .collect();
+            if eliminations.is_empty() {
+                continue;
+            }
+
+            let description = format!(
+                "{} in {} lies only in {}, remove it from {}",
+                value,
+                house_name(line),
+                house_name(b),
+                positions(&eliminated_cells(&eliminations))
+            );
+            return Some(Hint {
+                technique: Technique::BoxLineReduction,
+                cells: cells(&HOUSES[line], places),
+                values: vec![value],
+                placement: None,
+                eliminations,
+                description,
+            });
+        }
+    }
+    None
+}
+
+// Two cells of a house holding the same two candidates.
+fn naked_pair(candidates: &Candidates) -> Option<Hint> {
+    for (index, house) in HOUSES.iter().enumerate() {
+        for i in 0..9 {
+            let (ay, ax) = house[i];
+            if candidates.board.value(ay, ax) != 0 || candidates.count(ay, ax) != 2 {
+                continue;
+            }
+            for j in (i + 1)..9 {
+                let (by, bx) = house[j];
+                if candidates.options[ay][ax] != candidates.options[by][bx] {
+                    continue;
+                }
+
+                let values: Vec<usize> = bitboard::values(candidates.options[ay][ax]).collect();
+                let mut eliminations = Vec::new();
+                for (y, x) in house {
+                    if (*y, *x) == (ay, ax) || (*y, *x) == (by, bx) {
+                        continue;
+                    }
+                    for value in &values {
+                        if candidates.has((*y, *x), *value) {
+                            eliminations.push((*y, *x, *value));
+                        }
+                    }
+                }
+                if eliminations.is_empty() {
+                    continue;
+                }
+
+                let description = format!(
+                    "{} hold {} and {} in {}, remove them from {}",
+                    positions(&[(ay, ax), (by, bx)]),
+                    values[0],
+                    values[1],
+                    house_name(index),
+                    positions(&eliminated_cells(&eliminations))
+                );
+                return Some(Hint {
+                    technique: Technique::NakedPair,
+                    cells: vec![(ay, ax), (by, bx)],
+                    values,
+                    placement: None,
+                    eliminations,
+                    description,
+                });
+            }
+        }
+    }
+    None
+}
+
+// Two values of a house that fit only the same two cells.
+fn hidden_pair(candidates: &Candidates) -> Option<Hint> {
+    for (index, house) in HOUSES.iter().enumerate() {
+        let mut places = [0; 9];
+        for value in 1..10 {
+            places[value - 1] = candidates.places(house, value);
+        }
+
+        for a in 0..9 {
+            if places[a].count_ones() != 2 {
+                continue;
+            }
+            for b in (a + 1)..9 {
+                if places[a] != places[b] {
+                    continue;
+                }
+
+                let pair = cells(house, places[a]);
+                let mut eliminations = Vec::new();
+                for (y, x) in &pair {
+                    for value in 1..10 {
+                        if value != a + 1 && value != b + 1 && candidates.has((*y, *x), value) {
+                            eliminations.push((*y, *x, value));
+                        }
+                    }
+                }
+                if eliminations.is_empty() {
+                    continue;
+                }
+
+                let description = format!(
+                    "{} and {} fit only {} in {}, remove other candidates",
+                    a + 1,
+                    b + 1,
+                    positions(&pair),
+                    house_name(index)
+                );
+                return Some(Hint {
+                    technique: Technique::HiddenPair,
+                    cells: pair,
+                    values: vec![a + 1, b + 1],
+                    placement: None,
+                    eliminations,
+                    description,
+                });
+            }
+        }
+    }
+    None
+}
+
+// Two rows (or columns) where a value fits the same two columns (or rows).
+fn x_wing(candidates: &Candidates) -> Option<Hint> {
+    for value in 1..10 {
+        for (base, cover) in [(0, 9), (9, 0)] {
+            let mut places = [0; 9];
+            for line in 0..9 {
+                places[line] = candidates.places(&HOUSES[base + line], value);
+            }
+
+            for a in 0..9 {
+                if places[a].count_ones() != 2 {
+                    continue;
+                }
+                for b in (a + 1)..9 {
+                    if places[a] != places[b] {
+                        continue;
+                    }
+
+                    let covers: Vec<usize> = indices(places[a]).collect();
+                    let mut eliminations = Vec::new();
+                    for i in &covers {
+                        for (line, (y, x)) in HOUSES[cover + i].iter().enumerate() {
+                            if line != a && line != b && candidates.has((*y, *x), value) {
+                                eliminations.push((*y, *x, value));
+                            }
+                        }
+                    }
+                    if eliminations.is_empty() {
+                        continue;
+                    }
+
+                    let mut cells = Vec::new();
+                    for line in [a, b] {
+                        for i in &covers {
+                            cells.push(HOUSES[base + line][*i]);
+                        }
+                    }
+                    let description = format!(
+                        "{} in {} and {} lies only in {} and {}, remove it from {}",
+                        value,
+                        house_name(base + a),
+                        house_name(base + b),
+                        house_name(cover + covers[0]),
+                        house_name(cover + covers[1]),
+                        positions(&eliminated_cells(&eliminations))
+                    );
+                    return Some(Hint {
+                        technique: Technique::XWing,
+                        cells,
+                        values: vec![value],
+                        placement: None,
+                        eliminations,
+                        description,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::PUZZLE,
+        solver::{self, Solution},
+    };
+
+    // between them these need every technique
+    const PUZZLES: [&str; 2] = [
+        ".7..8...9.154.......3.7.6.....5..9..13.9.7.24..9..8.....1.3.8.......219.7...5..3.",
+        "..2....9.3...6.8.....25..1..3......5..94.16..6......4..1..45.....5.9...7.7....3..",
+    ];
+
+    #[test]
+    fn simplest_first() {
+        let hint = find(&Grid::parse(PUZZLE).unwrap()).unwrap();
+        assert_eq!(hint.technique, Technique::HiddenSingle);
+        assert_eq!(hint.describe(), "hidden single: 5 fits only r3c7 in row 3");
+    }
+
+    // Every hint has to agree with the solution: placements put its value,
+    // eliminations never take it away.
+    #[test]
+    fn every_technique() {
+        let mut used = Vec::new();
+        for puzzle in PUZZLES {
+            let grid = Grid::parse(puzzle).unwrap();
+            let solution = match solver::solve(&grid) {
+                Solution::Unique(solution) => solution,
+                _ => panic!("expected a unique solution"),
+            };
+            let mut candidates = Candidates::from(&grid);
+            while let Some(hint) = next(&candidates) {
+                if let Some((y, x, value)) = hint.placement {
+                    assert_eq!(solution[y][x], value, "{}", hint.describe());
+                }
+                for (y, x, value) in &hint.eliminations {
+                    assert_ne!(solution[*y][*x], *value, "{}", hint.describe());
+                }
+                assert!(hint.placement.is_some() || !hint.eliminations.is_empty());
+                if !used.contains(&hint.technique) {
+                    used.push(hint.technique);
+                }
+                candidates.apply(&hint);
+            }
+            assert!(candidates.is_solved());
+        }
+        used.sort();
+        assert_eq!(
+            used,
+            [
+                Technique::HiddenSingle,
+                Technique::NakedSingle,
+                Technique::PointingPair,
+                Technique::BoxLineReduction,
+                Technique::NakedPair,
+                Technique::XWing,
+                Technique::HiddenPair,
+            ]
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::mem;
+
+use crate::grid::Grid;
+
+// Grids from before each change, so values, notes and colors can be restored.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Grid>,
+    redo: Vec<Grid>,
+}
+
+impl History {
+    pub fn record(&mut self, before: Grid) {
+        self.undo.push(before);
+        self.redo.clear();
+    }
+
+    pub fn undo(&mut self, grid: &mut Grid) -> bool {
+        match self.undo.pop() {
+            Some(previous) => {
+                self.redo.push(mem::replace(grid, previous));
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, grid: &mut Grid) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push(mem::replace(grid, next));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with(value: usize) -> Grid {
+        let mut grid = Grid::default();
+        grid.cells[0][0].value = value;
+        grid
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::default();
+        let mut grid = with(0);
+        assert!(!history.undo(&mut grid));
+        assert!(!history.redo(&mut grid));
+
+        history.record(grid.clone());
+        grid = with(1);
+        history.record(grid.clone());
+        grid = with(2);
+
+        assert!(history.undo(&mut grid));
+        assert!(grid == with(1));
+        assert!(history.undo(&mut grid));
+        assert!(grid == with(0));
+        assert!(!history.undo(&mut grid));
+
+        assert!(history.redo(&mut grid));
+        assert!(grid == with(1));
+        assert!(history.redo(&mut grid));
+        assert!(grid == with(2));
+        assert!(!history.redo(&mut grid));
+    }
+
+    #[test]
+    fn record_drops_redo() {
+        let mut history = History::default();
+        let mut grid = with(1);
+        history.record(with(0));
+        assert!(history.undo(&mut grid));
+
+        history.record(grid.clone());
+        grid = with(3);
+        assert!(!history.redo(&mut grid));
+        assert!(history.undo(&mut grid));
+        assert!(grid == with(0));
+    }
+}
@@ -0,0 +1,558 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use sudoku_rust::{config, error::Error};
+
+pub const FILE: &str = "keys.toml";
+
+// Everything a key can do besides typing numbers. The order is the order of
+// the help overlay.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    JumpUp,
+    JumpDown,
+    JumpLeft,
+    JumpRight,
+    ExtendUp,
+    ExtendDown,
+    ExtendLeft,
+    ExtendRight,
+    InsertMode,
+    NoteMode,
+    MarkMode,
+    MarkColorMode,
+    HighlightOnlyMode,
+    HighlightMode,
+    SelectMode,
+    PaintMode,
+    Paint,
+    Unpaint,
+    ClearPaint,
+    ResetColors,
+    NextTheme,
+    Undo,
+    Redo,
+    Menu,
+    Pause,
+    Cancel,
+    Help,
+    Quit,
+}
+
+impl Action {
+    pub const ALL: [Action; 32] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::JumpUp,
+        Action::JumpDown,
+        Action::JumpLeft,
+        Action::JumpRight,
+        Action::ExtendUp,
+        Action::ExtendDown,
+        Action::ExtendLeft,
+        Action::ExtendRight,
+        Action::InsertMode,
+        Action::NoteMode,
+        Action::MarkMode,
+        Action::MarkColorMode,
+        Action::HighlightOnlyMode,
+        Action::HighlightMode,
+        Action::SelectMode,
+        Action::PaintMode,
+        Action::Paint,
+        Action::Unpaint,
+        Action::ClearPaint,
+        Action::ResetColors,
+        Action::NextTheme,
+        Action::Undo,
+        Action::Redo,
+        Action::Menu,
+        Action::Pause,
+        Action::Cancel,
+        Action::Help,
+        Action::Quit,
+    ];
+
+    // name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::JumpUp => "jump_up",
+            Action::JumpDown => "jump_down",
+            Action::JumpLeft => "jump_left",
+            Action::JumpRight => "jump_right",
+            Action::ExtendUp => "extend_up",
+            Action::ExtendDown => "extend_down",
+            Action::ExtendLeft => "extend_left",
+            Action::ExtendRight => "extend_right",
+            Action::InsertMode => "insert_mode",
+            Action::NoteMode => "note_mode",
+            Action::MarkMode => "mark_mode",
+            Action::MarkColorMode => "mark_color_mode",
+            Action::HighlightOnlyMode => "highlight_only_mode",
+            Action::HighlightMode => "highlight_mode",
+            Action::SelectMode => "select_mode",
+            Action::PaintMode => "paint_mode",
+            Action::Paint => "paint",
+            Action::Unpaint => "unpaint",
+            Action::ClearPaint => "clear_paint",
+            Action::ResetColors => "reset_colors",
+            Action::NextTheme => "next_theme",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Menu => "menu",
+            Action::Pause => "pause",
+            Action::Cancel => "cancel",
+            Action::Help => "help",
+            Action::Quit => "quit",
+        }
+    }
+
+    pub fn from(name: &str) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move up",
+            Action::MoveDown => "move down",
+            Action::MoveLeft => "move left",
+            Action::MoveRight => "move right",
+            Action::JumpUp => "move three cells up",
+            Action::JumpDown => "move three cells down",
+            Action::JumpLeft => "move three cells left",
+            Action::JumpRight => "move three cells right",
+            Action::ExtendUp => "extend selection up",
+            Action::ExtendDown => "extend selection down",
+            Action::ExtendLeft => "extend selection left",
+            Action::ExtendRight => "extend selection right",
+            Action::InsertMode => "insert mode",
+            Action::NoteMode => "note mode",
+            Action::MarkMode => "mark mode",
+            Action::MarkColorMode => "mark color selection",
+            Action::HighlightOnlyMode => "highlight mode",
+            Action::HighlightMode => "multi highlight mode",
+            Action::SelectMode => "select mode",
+            Action::PaintMode => "paint mode",
+            Action::Paint => "paint cells (paint mode)",
+            Action::Unpaint => "clear paint (paint mode)",
+            Action::ClearPaint => "clear all paint",
+            Action::ResetColors => "reset colors",
+            Action::NextTheme => "switch to the next theme",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::Menu => "open or close the menu",
+            Action::Pause => "pause the timer, hiding the board",
+            Action::Cancel => "close menu, clear selection",
+            Action::Help => "show or hide this help",
+            Action::Quit => "quit",
+        }
+    }
+}
+
+// A key and the modifiers held with it. Shift is part of the character for
+// printable keys, `W` rather than Shift-w, and only kept for the others.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Key {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl Key {
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub const fn char(char: char) -> Self {
+        Self::new(KeyCode::Char(char))
+    }
+
+    pub const fn alt(char: char) -> Self {
+        Self {
+            code: KeyCode::Char(char),
+            modifiers: KeyModifiers::ALT,
+        }
+    }
+
+    pub const fn ctrl(char: char) -> Self {
+        Self {
+            code: KeyCode::Char(char),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    pub const fn shift(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::SHIFT,
+        }
+    }
+
+    // held with Ctrl or Alt, so not something typed
+    pub fn is_chord(&self) -> bool {
+        self.modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers;
+        if matches!(event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+pub struct Keymap {
+    bindings: Vec<(Key, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: vec![
+                (Key::char('w'), Action::MoveUp),
+                (Key::new(KeyCode::Up), Action::MoveUp),
+                (Key::char('s'), Action::MoveDown),
+                (Key::new(KeyCode::Down), Action::MoveDown),
+                (Key::char('a'), Action::MoveLeft),
+                (Key::new(KeyCode::Left), Action::MoveLeft),
+                (Key::char('d'), Action::MoveRight),
+                (Key::new(KeyCode::Right), Action::MoveRight),
+                (Key::char('W'), Action::JumpUp),
+                (Key::char('S'), Action::JumpDown),
+                (Key::char('A'), Action::JumpLeft),
+                (Key::char('D'), Action::JumpRight),
+                (Key::alt('w'), Action::ExtendUp),
+                (Key::shift(KeyCode::Up), Action::ExtendUp),
+                (Key::alt('s'), Action::ExtendDown),
+                (Key::shift(KeyCode::Down), Action::ExtendDown),
+                (Key::alt('a'), Action::ExtendLeft),
+                (Key::shift(KeyCode::Left), Action::ExtendLeft),
+                (Key::alt('d'), Action::ExtendRight),
+                (Key::shift(KeyCode::Right), Action::ExtendRight),
+                (Key::char('i'), Action::InsertMode),
+                (Key::char('n'), Action::NoteMode),
+                (Key::char('m'), Action::MarkMode),
+                (Key::char('M'), Action::MarkColorMode),
+                (Key::char('h'), Action::HighlightOnlyMode),
+                (Key::char('H'), Action::HighlightMode),
+                (Key::char('v'), Action::SelectMode),
+                (Key::char('b'), Action::PaintMode),
+                (Key::char(' '), Action::Paint),
+                (Key::new(KeyCode::Enter), Action::Paint),
+                (Key::new(KeyCode::Backspace), Action::Unpaint),
+                (Key::new(KeyCode::Delete), Action::Unpaint),
+                (Key::char('B'), Action::ClearPaint),
+                (Key::char('c'), Action::ResetColors),
+                (Key::char('t'), Action::NextTheme),
+                (Key::char('u'), Action::Undo),
+                (Key::ctrl('r'), Action::Redo),
+                (Key::char('f'), Action::Menu),
+                (Key::char('p'), Action::Pause),
+                (Key::new(KeyCode::Esc), Action::Cancel),
+                (Key::char('?'), Action::Help),
+                (Key::ctrl('c'), Action::Quit),
+            ],
+        }
+    }
+}
+
+impl Keymap {
+    // hjkl moves, so the highlight modes move to `*` and `#`
+    pub fn vim() -> Self {
+        let mut keymap = Keymap::default();
+        keymap.bind(Action::MoveUp, vec![Key::char('k'), Key::new(KeyCode::Up)]);
+        keymap.bind(
+            Action::MoveDown,
+            vec![Key::char('j'), Key::new(KeyCode::Down)],
+        );
+        keymap.bind(
+            Action::MoveLeft,
+            vec![Key::char('h'), Key::new(KeyCode::Left)],
+        );
+        keymap.bind(
+            Action::MoveRight,
+            vec![Key::char('l'), Key::new(KeyCode::Right)],
+        );
+        keymap.bind(Action::JumpUp, vec![Key::char('K')]);
+        keymap.bind(Action::JumpDown, vec![Key::char('J')]);
+        keymap.bind(Action::JumpLeft, vec![Key::char('H')]);
+        keymap.bind(Action::JumpRight, vec![Key::char('L')]);
+        keymap.bind(
+            Action::ExtendUp,
+            vec![Key::alt('k'), Key::shift(KeyCode::Up)],
+        );
+        keymap.bind(
+            Action::ExtendDown,
+            vec![Key::alt('j'), Key::shift(KeyCode::Down)],
+        );
+        keymap.bind(
+            Action::ExtendLeft,
+            vec![Key::alt('h'), Key::shift(KeyCode::Left)],
+        );
+        keymap.bind(
+            Action::ExtendRight,
+            vec![Key::alt('l'), Key::shift(KeyCode::Right)],
+        );
+        keymap.bind(Action::HighlightOnlyMode, vec![Key::char('*')]);
+        keymap.bind(Action::HighlightMode, vec![Key::char('#')]);
+        keymap
+    }
+
+    pub fn profile(name: &str) -> Option<Self> {
+        match name {
+            "default" => Some(Keymap::default()),
+            "vim" => Some(Keymap::vim()),
+            _ => None,
+        }
+    }
+
+    // Replaces the keys of an action. The keys are taken away from any other
+    // action they were bound to.
+    pub fn bind(&mut self, action: Action, keys: Vec<Key>) {
+        self.bindings
+            .retain(|(key, bound)| *bound != action && !keys.contains(key));
+        for key in keys {
+            self.bindings.push((key, action));
+        }
+    }
+
+    // Reads the keymap from the config file, the default one if there is no
+    // file. The file picks a profile and rebinds actions:
+    //
+    //     profile = "vim"
+    //
+    //     [keys]
+    //     quit = "Ctrl-q"
+    //     note_mode = ["n", "Tab"]
+    pub fn load() -> Result<Self, Error> {
+        let (content, table) = match config::read(FILE)? {
+            Some(config) => config,
+            None => return Ok(Keymap::default()),
+        };
+        let error = |key: &str, message: String| Err(config::error(FILE, &content, key, message));
+
+        let mut keymap = match table.get("profile") {
+            None => Keymap::default(),
+            Some(toml::Value::String(name)) => match Keymap::profile(name) {
+                Some(keymap) => keymap,
+                None => return error("profile", format!("unknown profile `{}`", name)),
+            },
+            Some(_) => return error("profile", String::from("expected a profile name")),
+        };
+
+        let keys = match table.get("keys") {
+            None => return Ok(keymap),
+            Some(toml::Value::Table(keys)) => keys,
+            Some(_) => return error("keys", String::from("expected a [keys] table")),
+        };
+        for (name, value) in keys {
+            let action = match Action::from(name) {
+                Some(action) => action,
+                None => return error(name, format!("unknown action `{}`", name)),
+            };
+            let names: Option<Vec<&str>> = match value {
+                toml::Value::String(key) => Some(vec![key.as_str()]),
+                toml::Value::Array(keys) => keys.iter().map(|key| key.as_str()).collect(),
+                _ => None,
+            };
+            let names = match names {
+                Some(names) => names,
+                None => return error(name, String::from("expected a key or a list of keys")),
+            };
+            let mut bound = Vec::new();
+            for key in names {
+                match parse_key(key) {
+                    Some(key) => bound.push(key),
+                    None => return error(name, format!("unknown key `{}`", key)),
+                }
+            }
+            keymap.bind(action, bound);
+        }
+        Ok(keymap)
+    }
+
+    pub fn action(&self, key: Key) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, action)| *action)
+    }
+
+    // every key bound to the action, in the order they were bound
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        self.bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+}
+
+// modifiers first, as in `Ctrl-Alt-x` or `Shift-Up`
+pub fn key_name(key: Key) -> String {
+    let mut name = String::new();
+    for (modifier, prefix) in MODIFIERS {
+        if key.modifiers.contains(modifier) {
+            name += prefix;
+        }
+    }
+    name += &match key.code {
+        KeyCode::Char(' ') => String::from("Space"),
+        KeyCode::Char(char) => char.to_string(),
+        KeyCode::F(number) => format!("F{}", number),
+        KeyCode::Enter => String::from("Enter"),
+        KeyCode::Tab => String::from("Tab"),
+        KeyCode::BackTab => String::from("Shift-Tab"),
+        KeyCode::Up => String::from("Up"),
+        KeyCode::Down => String::from("Down"),
+        KeyCode::Left => String::from("Left"),
+        KeyCode::Right => String::from("Right"),
+        KeyCode::Backspace => String::from("Backspace"),
+        KeyCode::Delete => String::from("Delete"),
+        KeyCode::Insert => String::from("Insert"),
+        KeyCode::Home => String::from("Home"),
+        KeyCode::End => String::from("End"),
+        KeyCode::PageUp => String::from("PageUp"),
+        KeyCode::PageDown => String::from("PageDown"),
+        KeyCode::Esc => String::from("Esc"),
+        _ => String::from("?"),
+    };
+    name
+}
+
+const MODIFIERS: [(KeyModifiers, &str); 3] = [
+    (KeyModifiers::CONTROL, "Ctrl-"),
+    (KeyModifiers::ALT, "Alt-"),
+    (KeyModifiers::SHIFT, "Shift-"),
+];
+
+// reads the names written by `key_name`, case does not matter for the named
+// keys and modifiers
+pub fn parse_key(name: &str) -> Option<Key> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+    while let Some((modifier, prefix)) = MODIFIERS.into_iter().find(|(_, prefix)| {
+        rest.len() > prefix.len() && rest[..prefix.len()].eq_ignore_ascii_case(prefix)
+    }) {
+        modifiers |= modifier;
+        rest = &rest[prefix.len()..];
+    }
+
+    let lower = rest.to_lowercase();
+    let code = if let Some(char) = single(rest) {
+        KeyCode::Char(char)
+    } else if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        KeyCode::F(number)
+    } else {
+        match lower.as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "esc" => KeyCode::Esc,
+            _ => return None,
+        }
+    };
+
+    // shift is part of printable characters, and turns tab around
+    let code = match code {
+        KeyCode::Char(char) if modifiers.contains(KeyModifiers::SHIFT) => {
+            KeyCode::Char(char.to_ascii_uppercase())
+        }
+        KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+        code => code,
+    };
+    if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+    Some(Key { code, modifiers })
+}
+
+fn single(text: &str) -> Option<char> {
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(char), None) => Some(char),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        let keys = [
+            Key::char('w'),
+            Key::char('W'),
+            Key::char(' '),
+            Key::char('-'),
+            Key::ctrl('q'),
+            Key::ctrl('-'),
+            Key::alt('x'),
+            Key::new(KeyCode::F(5)),
+            Key::new(KeyCode::BackTab),
+            Key::new(KeyCode::PageDown),
+            Key::shift(KeyCode::Up),
+            Key {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            },
+        ];
+        for key in keys {
+            assert_eq!(parse_key(&key_name(key)), Some(key), "{}", key_name(key));
+        }
+        assert_eq!(key_name(Key::ctrl('q')), "Ctrl-q");
+        assert_eq!(key_name(Key::new(KeyCode::BackTab)), "Shift-Tab");
+    }
+
+    #[test]
+    fn any_case() {
+        assert_eq!(parse_key("SPACE"), Some(Key::char(' ')));
+        assert_eq!(parse_key("f5"), Some(Key::new(KeyCode::F(5))));
+        assert_eq!(
+            parse_key("alt-ctrl-left"),
+            Some(Key {
+                code: KeyCode::Left,
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            })
+        );
+        // characters keep their case, shift being one way to write it
+        assert_eq!(parse_key("shift-w"), Some(Key::char('W')));
+        assert_eq!(parse_key("shift-tab"), Some(Key::new(KeyCode::BackTab)));
+    }
+
+    #[test]
+    fn unknown() {
+        for name in ["", "Ctrl-", "Hyper-x", "Fx", "Escape", "ab"] {
+            assert_eq!(parse_key(name), None, "{}", name);
+        }
+    }
+}
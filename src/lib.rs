@@ -0,0 +1,32 @@
+// The puzzle without the game: grids and their cells, the board with its
+// cursor, selection and undo history, solving, hints, generating and rating
+// puzzles, the save format and themes. None of it needs a terminal, the
+// widget only draws into a `tui` buffer.
+//
+// Modules allowing `needless_range_loop` walk 9x9 arrays by row and column,
+// which reads better with the indices than with iterators.
+pub mod batch;
+#[allow(clippy::needless_range_loop)]
+pub mod bitboard;
+#[allow(clippy::needless_range_loop)]
+pub mod board;
+pub mod cell;
+pub mod cell_options;
+pub mod config;
+pub mod error;
+#[cfg(test)]
+mod fixtures;
+pub mod format;
+pub mod generator;
+#[allow(clippy::needless_range_loop)]
+pub mod grid;
+#[allow(clippy::needless_range_loop)]
+pub mod hint;
+mod history;
+pub mod rater;
+pub mod solver;
+#[allow(clippy::needless_range_loop)]
+pub mod sync;
+pub mod theme;
+#[allow(clippy::needless_range_loop)]
+pub mod widget;
@@ -1,64 +1,347 @@
-mod board;
-mod cell;
-mod cell_options;
-mod column;
 mod events;
-mod grid;
-mod row;
-mod sync;
-mod theme;
+mod keymap;
 mod ui;
 
 use crate::events::Events;
 use core::time;
 use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
+    event::{DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use grid::Grid;
-use std::env;
-use std::{io, thread};
-use sync::load;
+use keymap::Keymap;
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    panic,
+    path::Path,
+    process, thread,
+};
+use sudoku_rust::{
+    batch::{self, Entry},
+    error::Error,
+    format::Format,
+    generator::{self, Difficulty},
+    grid::Grid,
+    rater,
+    solver::{self, Solution},
+    sync::Session,
+    theme,
+};
 use tui::{backend::CrosstermBackend, Terminal};
 use ui::UI;
 
-fn main() -> Result<(), io::Error> {
-    let args: Vec<String> = env::args().collect();
-    let input = &args[1];
+const USAGE: &str = "usage: sudoku-rust [play] <puzzle>
+       sudoku-rust [play] --difficulty <difficulty> [--seed <number>]
+       sudoku-rust solve <puzzle> [--from <format>] [--to <format>]
+       sudoku-rust generate --difficulty <difficulty> [--seed <number>] [--to <format>]
+       sudoku-rust rate <puzzle> [--from <format>]
+       sudoku-rust convert <puzzle> [--from <format>] --to <format>
+       sudoku-rust batch <file> [--report csv|json]
+       sudoku-rust --help | --version
 
-    let grid: Grid;
-    if input.ends_with(".sudoku") {
-        grid = load(input);
-    } else if input.len() == 81 {
-        grid = Grid::from(input.to_string());
-    } else {
-        return Ok(());
+<puzzle> is a file, 81 cells with 0, `.` or `_` for empty ones, or `-` to read
+standard input. Without `--from` files are read by their extension, `.sudoku`
+as saved games and anything else as text: a line, a 9x9 grid, `.sdk` or `.ss`.
+
+<difficulty> is easy, medium, hard or expert
+<format> is line, grid, sdk, ss or sudoku; puzzles are printed as a line
+unless `--to` says otherwise
+
+batch checks a puzzle per line of <file>, or of standard input for `-`, and
+prints whether each has a unique solution, several or none as csv or json";
+
+const COMMANDS: [&str; 6] = ["play", "solve", "generate", "rate", "convert", "batch"];
+
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("error: {}", error);
+        process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        return output(&format!("{}\n", USAGE));
+    }
+    if args.iter().any(|arg| arg == "--version" || arg == "-V") {
+        return output(&format!("sudoku-rust {}\n", env!("CARGO_PKG_VERSION")));
+    }
+
+    // a puzzle without a command opens the game
+    let (command, args) = match args.first() {
+        Some(command) if COMMANDS.contains(&command.as_str()) => (command.as_str(), &args[1..]),
+        Some(_) => ("play", &args[..]),
+        None => return Err(Error::Usage(USAGE.to_string())),
+    };
+
+    match command {
+        "solve" => {
+            let options = Options::parse(args, &["--from", "--to"])?;
+            let (grid, _) = read_puzzle(options.puzzle()?, options.format("--from")?)?;
+            let solution = Grid::from_values(&unique_solution(&grid)?);
+            let to = options.format("--to")?.unwrap_or(Format::Line);
+            output(&to.write(&solution, &Session::default()))?;
+        }
+        "generate" => {
+            let options = Options::parse(args, &["--difficulty", "--seed", "--to"])?;
+            let difficulty = match options.difficulty()? {
+                Some(difficulty) => difficulty,
+                None => return Err(Error::Usage(String::from("generate needs --difficulty"))),
+            };
+            let seed = options.seed()?;
+            let (grid, reached) = generator::generate(difficulty, seed);
+            let to = options.format("--to")?.unwrap_or(Format::Line);
+            output(&to.write(&grid, &Session::default()))?;
+            if reached != difficulty {
+                eprintln!(
+                    "warning: {}",
+                    generator::describe(difficulty, reached, seed)
+                );
+            }
+        }
+        "rate" => {
+            let options = Options::parse(args, &["--from"])?;
+            let (grid, _) = read_puzzle(options.puzzle()?, options.format("--from")?)?;
+            unique_solution(&grid)?;
+            output(&format!("{}\n", rater::rate(&grid).describe()))?;
+        }
+        "convert" => {
+            let options = Options::parse(args, &["--from", "--to"])?;
+            let to = match options.format("--to")? {
+                Some(to) => to,
+                None => return Err(Error::Usage(String::from("convert needs --to"))),
+            };
+            let (grid, session) = read_puzzle(options.puzzle()?, options.format("--from")?)?;
+            output(&to.write(&grid, &session))?;
+        }
+        "batch" => {
+            let options = Options::parse(args, &["--report"])?;
+            let report: fn(&[Entry]) -> String = match options.get("--report") {
+                None | Some("csv") => batch::csv,
+                Some("json") => batch::json,
+                Some(other) => {
+                    return Err(Error::Usage(format!(
+                        "unknown report {}, expected csv or json",
+                        other
+                    )))
+                }
+            };
+            let entries = batch::check(&read_input(options.puzzle()?)?);
+            output(&report(&entries))?;
+            eprintln!("{}", batch::summary(&entries));
+        }
+        _ => {
+            let options = Options::parse(args, &["--difficulty", "--seed"])?;
+
+            let (themes, theme) = theme::load()?;
+
+            let grid: Grid;
+            let mut session = Session::default();
+            let mut message = String::new();
+            let mut path = None;
+            if let Some(difficulty) = options.difficulty()? {
+                let seed = options.seed()?;
+                let reached;
+                (grid, reached) = generator::generate(difficulty, seed);
+                message = generator::describe(difficulty, reached, seed);
+            } else {
+                let puzzle = options.puzzle()?;
+                (grid, session) = read_puzzle(puzzle, None)?;
+                if Format::of_path(puzzle) == Some(Format::Sudoku) {
+                    path = Some(puzzle.to_string());
+                }
+            }
+
+            let mut ui = UI::from(grid);
+            ui.set_keymap(Keymap::load()?);
+            ui.set_themes(themes, theme);
+            ui.restore(session);
+            if let Some(path) = path {
+                ui.set_path(path);
+            }
+            ui.show_message(message);
+
+            play(ui)?;
+        }
+    }
+    Ok(())
+}
+
+// Arguments of a command, `--name value` options and the rest in order.
+struct Options<'a> {
+    values: Vec<(&'a str, &'a str)>,
+    rest: Vec<&'a str>,
+}
+
+impl<'a> Options<'a> {
+    // every option takes a value, unknown ones are an error
+    fn parse(args: &'a [String], known: &[&str]) -> Result<Self, Error> {
+        let mut options = Options {
+            values: Vec::new(),
+            rest: Vec::new(),
+        };
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if !arg.starts_with("--") {
+                options.rest.push(arg);
+                continue;
+            }
+            if !known.contains(&arg.as_str()) {
+                return Err(Error::Usage(format!("unknown option {}", arg)));
+            }
+            match args.next() {
+                Some(value) => options.values.push((arg, value)),
+                None => return Err(Error::Usage(format!("{} needs a value", arg))),
+            }
+        }
+        Ok(options)
+    }
+
+    fn get(&self, name: &str) -> Option<&'a str> {
+        self.values
+            .iter()
+            .rev()
+            .find(|(option, _)| *option == name)
+            .map(|(_, value)| *value)
+    }
+
+    // the single argument that is not an option
+    fn puzzle(&self) -> Result<&'a str, Error> {
+        match self.rest.as_slice() {
+            [puzzle] => Ok(puzzle),
+            [] => Err(Error::Usage(String::from("missing <puzzle>"))),
+            [_, extra, ..] => Err(Error::Usage(format!("unexpected argument {}", extra))),
+        }
     }
 
+    fn format(&self, name: &str) -> Result<Option<Format>, Error> {
+        match self.get(name) {
+            None => Ok(None),
+            Some(value) => match Format::from(value) {
+                Some(format) => Ok(Some(format)),
+                None => Err(Error::Usage(format!(
+                    "unknown format {}, expected line, grid, sdk, ss or sudoku",
+                    value
+                ))),
+            },
+        }
+    }
+
+    fn difficulty(&self) -> Result<Option<Difficulty>, Error> {
+        match self.get("--difficulty") {
+            None => Ok(None),
+            Some(value) => match Difficulty::from(value) {
+                Some(difficulty) => Ok(Some(difficulty)),
+                None => Err(Error::Usage(format!(
+                    "unknown difficulty {}, expected easy, medium, hard or expert",
+                    value
+                ))),
+            },
+        }
+    }
+
+    // a random one unless `--seed` is given
+    fn seed(&self) -> Result<u64, Error> {
+        match self.get("--seed") {
+            None => Ok(generator::random_seed()),
+            Some(value) => value
+                .parse()
+                .map_err(|_| Error::Usage(String::from("--seed needs a number"))),
+        }
+    }
+}
+
+fn unique_solution(grid: &Grid) -> Result<[[usize; 9]; 9], Error> {
+    match solver::solve(grid) {
+        Solution::Unique(solution) => Ok(*solution),
+        Solution::Multiple => Err(Error::Solution(String::from(
+            "puzzle has multiple solutions",
+        ))),
+        Solution::None => Err(Error::Solution(String::from("puzzle has no solution"))),
+    }
+}
+
+fn play(mut ui: UI) -> Result<(), Error> {
+    // put the terminal back before a panic message is printed
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableFocusChange
+    )?;
     let backend = CrosstermBackend::new(stdout);
-    let terminal = Terminal::new(backend)?;
+    let mut terminal = Terminal::new(backend)?;
 
     let events = Events::new();
 
-    let mut ui = UI::from(grid);
+    let result = ui.run(&mut terminal, events);
+    if result.is_ok() {
+        thread::sleep(time::Duration::from_millis(2000));
+    }
 
-    let mut terminal = ui.run(terminal, events);
+    restore_terminal()?;
+    terminal.show_cursor()?;
 
-    thread::sleep(time::Duration::from_millis(2000));
+    Ok(result?)
+}
 
-    // restore terminal
+fn restore_terminal() -> Result<(), io::Error> {
     execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableFocusChange
     )?;
-    terminal.show_cursor()?;
-    disable_raw_mode()?;
+    disable_raw_mode()
+}
 
-    Ok(())
+// Writes to standard output, a pipe closed early (`| head`) is not an error.
+fn output(text: &str) -> Result<(), Error> {
+    match io::stdout().write_all(text.as_bytes()) {
+        Err(error) if error.kind() != io::ErrorKind::BrokenPipe => {
+            Err(Error::File(String::from("<stdout>"), error))
+        }
+        _ => Ok(()),
+    }
+}
+
+// A file, `-` for standard input or the puzzle itself, read in the given
+// format or the one of the file extension.
+fn read_puzzle(input: &str, from: Option<Format>) -> Result<(Grid, Session), Error> {
+    let format = from
+        .or_else(|| Format::of_path(input))
+        .unwrap_or(Format::Line);
+    if input == "-" {
+        format.read(&read_input(input)?)
+    } else if format == Format::Sudoku || Path::new(input).is_file() {
+        format
+            .read(&read_input(input)?)
+            .map_err(|error| error.in_file(input))
+    } else {
+        format.read(input)
+    }
+}
+
+// content of a file, or of standard input for `-`
+fn read_input(input: &str) -> Result<String, Error> {
+    if input == "-" {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|error| Error::File(String::from("<stdin>"), error))?;
+        Ok(content)
+    } else {
+        fs::read_to_string(input).map_err(|error| Error::File(input.to_string(), error))
+    }
 }
@@ -0,0 +1,105 @@
+use crate::{
+    generator::Difficulty,
+    grid::Grid,
+    hint::{self, Candidates, Technique},
+};
+
+pub struct Rating {
+    // hardest technique needed, None if the techniques run out before the end
+    pub technique: Option<Technique>,
+    pub score: f32,
+    pub difficulty: Difficulty,
+}
+
+impl Rating {
+    pub fn describe(&self) -> String {
+        match self.technique {
+            Some(_) => format!("{:.1} {}", self.score, self.difficulty.name()),
+            None => format!("{:.1}+ {}", self.score, self.difficulty.name()),
+        }
+    }
+}
+
+// Rates the puzzle given by the initial cells by solving it with the simplest
+// technique available at every step and keeping the hardest one used. Puzzles
+// the techniques can not finish get the highest known score and are marked
+// with a `+`.
+pub fn rate(grid: &Grid) -> Rating {
+    let mut candidates = Candidates::from(&Grid::from_values(&grid.givens()));
+    let mut hardest = Technique::HiddenSingle;
+    while let Some(hint) = hint::next(&candidates) {
+        hardest = hardest.max(hint.technique);
+        candidates.apply(&hint);
+    }
+
+    let technique = if candidates.is_solved() {
+        Some(hardest)
+    } else {
+        None
+    };
+    Rating {
+        technique,
+        score: technique.unwrap_or(Technique::HiddenPair).score(),
+        difficulty: Difficulty::of(technique),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::PUZZLE;
+
+    fn describe(puzzle: &str) -> String {
+        rate(&Grid::parse(puzzle).unwrap()).describe()
+    }
+
+    #[test]
+    fn tiers() {
+        assert_eq!(describe(PUZZLE), "1.5 easy");
+        assert_eq!(
+            describe(
+                ".54...192.....3846..6....5..3794.21....3.5....98.7146..6....5..7134.....945...72."
+            ),
+            "1.5 easy"
+        );
+        assert_eq!(
+            describe(
+                "...85.4..7..4.128..4.....7.4.....7..13.2.6.45..5.....2.7.....3..143.2..7..3.75..."
+            ),
+            "2.6 medium"
+        );
+        assert_eq!(
+            describe(
+                ".4.5....8.76....3.2..1....4.....9...5.94.17.3...7.....7....8..1.8....46.3....4.9."
+            ),
+            "3.0 hard"
+        );
+        assert_eq!(
+            describe(
+                ".431.....71..2..3..59.731.........7.3..8.2..9.9.........153.26..3..4..95.....631."
+            ),
+            "3.4 hard"
+        );
+    }
+
+    // what the techniques can not finish is expert, scored past the hardest one
+    #[test]
+    fn beyond_techniques() {
+        let grid = Grid::parse(
+            "......795.2.7..4......64.2....12..8..18...65..4..86....5.23......2..1.6.793......",
+        )
+        .unwrap();
+        let rating = rate(&grid);
+        assert!(rating.technique.is_none());
+        assert_eq!(rating.difficulty, Difficulty::Expert);
+        assert_eq!(rating.describe(), "3.4+ expert");
+    }
+
+    // only the initial cells count, not what has been filled in since
+    #[test]
+    fn givens_only() {
+        let mut grid = Grid::parse(PUZZLE).unwrap();
+        grid.cells[0][2].value = 4;
+        assert_eq!(rate(&grid).describe(), "1.5 easy");
+    }
+}
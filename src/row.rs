@@ -1,24 +0,0 @@
-use crate::{cell::Cell, grid::Grid};
-
-#[derive(Default)]
-pub struct Row {
-    // cells: [Cell; 9],
-}
-
-impl Row {
-    // fn from(grid: Grid, index: usize) -> Self {
-    //     Self {
-    //         cells: [
-    //             grid.cell_at(index, 1),
-    //             grid.cell_at(index, 2),
-    //             grid.cell_at(index, 3),
-    //             grid.cell_at(index, 4),
-    //             grid.cell_at(index, 5),
-    //             grid.cell_at(index, 6),
-    //             grid.cell_at(index, 7),
-    //             grid.cell_at(index, 8),
-    //             grid.cell_at(index, 9),
-    //         ],
-    //     }
-    // }
-}
@@ -0,0 +1,105 @@
+use crate::{
+    bitboard::{self, Bitboard},
+    grid::Grid,
+};
+
+pub enum Solution {
+    None,
+    Unique(Box<[[usize; 9]; 9]>),
+    Multiple,
+}
+
+// Solves the puzzle given by the initial cells of the grid, ignoring values
+// entered since.
+pub fn solve(grid: &Grid) -> Solution {
+    solve_values(&grid.givens())
+}
+
+pub fn solve_values(values: &[[usize; 9]; 9]) -> Solution {
+    let found = search(values, 2);
+    match found.len() {
+        0 => Solution::None,
+        1 => Solution::Unique(Box::new(found[0])),
+        _ => Solution::Multiple,
+    }
+}
+
+// Collects up to `limit` solutions of the values.
+pub fn search(values: &[[usize; 9]; 9], limit: usize) -> Vec<[[usize; 9]; 9]> {
+    let mut found = Vec::new();
+    let board = Bitboard::from_values(values);
+    if board.is_consistent() {
+        backtrack(&board, limit, &mut found);
+    }
+    found
+}
+
+// true if no value repeats in a row, column or box
+pub fn is_consistent(values: &[[usize; 9]; 9]) -> bool {
+    Bitboard::from_values(values).is_consistent()
+}
+
+// Boards are small enough to copy for every guess, placing a value only
+// updates the candidates it touches.
+fn backtrack(board: &Bitboard, limit: usize, found: &mut Vec<[[usize; 9]; 9]>) {
+    // continue from the empty cell with the fewest options
+    let (y, x) = match board.fewest() {
+        Some(cell) => cell,
+        None => {
+            found.push(board.values());
+            return;
+        }
+    };
+
+    for value in bitboard::values(board.candidates(y, x)) {
+        let mut next = *board;
+        next.set_value(y, x, value);
+        backtrack(&next, limit, found);
+        if found.len() >= limit {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{line, PUZZLE, SOLUTION};
+
+    #[test]
+    fn unique() {
+        match solve(&Grid::parse(PUZZLE).unwrap()) {
+            Solution::Unique(solution) => assert_eq!(line(&solution), SOLUTION),
+            _ => panic!("expected a unique solution"),
+        }
+    }
+
+    #[test]
+    fn multiple() {
+        assert!(matches!(solve(&Grid::default()), Solution::Multiple));
+        // only the first three rows of the puzzle
+        let puzzle = format!("{}{}", &PUZZLE[..27], ".".repeat(54));
+        assert!(matches!(
+            solve(&Grid::parse(&puzzle).unwrap()),
+            Solution::Multiple
+        ));
+    }
+
+    #[test]
+    fn none() {
+        // two 5s in the first row
+        let clash = PUZZLE.replacen('.', "5", 1);
+        assert!(!is_consistent(&Grid::parse(&clash).unwrap().givens()));
+        assert!(matches!(
+            solve(&Grid::parse(&clash).unwrap()),
+            Solution::None
+        ));
+
+        // no value fits the end of the first row, though nothing repeats
+        let mut values = [[0; 9]; 9];
+        values[0] = [1, 2, 3, 4, 5, 6, 7, 8, 0];
+        values[4][8] = 9;
+        assert!(is_consistent(&values));
+        assert!(matches!(solve_values(&values), Solution::None));
+    }
+}
@@ -1,85 +1,356 @@
+use crate::error::Error;
 use crate::grid::Grid;
-use std::fs::File;
+use crate::theme::Slot;
+use std::fs::{self, File};
 use std::io::Write;
-use std::io::{prelude::*, BufReader};
-
-pub fn save(grid: &Grid) {
-    let mut file = File::create("recent.sudoku").expect("file creation failed");
-
-    let mut y = 0;
-    loop {
-        let mut x = 0;
-        loop {
-            if grid.cells[y][x].value != 0 {
-                file.write_all(grid.cells[y][x].value.to_string().as_bytes())
-                    .expect("cannot save sudoku");
-                file.write_all("-".as_bytes()).expect("cannot save sudoku");
-                if grid.cells[y][x].initial {
-                    file.write_all("i".as_bytes()).expect("cannot save sudoku");
+
+pub const VERSION: usize = 2;
+
+// State written next to the grid so a board reopens the way it was left.
+pub struct Session {
+    pub current_position: (usize, usize),
+    pub mode: String,
+    pub mark_selected_color: Slot,
+    // seconds spent on the puzzle so far
+    pub time: u64,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            current_position: (0, 0),
+            mode: String::from("insert"),
+            mark_selected_color: Slot::Purple,
+            time: 0,
+        }
+    }
+}
+
+// Version 2 files start with a header and the session, the time spent on
+// the puzzle in seconds included, followed by one line per cell. Cell lines
+// are version 1 lines (`value-i` or `0-notes`) followed by the colors that
+// differ from the defaults, options by their number, and the `paint` of the
+// whole cell. Colors are theme slots, so the board looks right in any theme:
+//
+//     sudoku 2
+//     cursor 4 7
+//     mode note
+//     mark purple
+//     time 754
+//     5-i bg=blue fg=black
+//     0-137 paint=green 3=purple/black
+//
+// Files without the header are read as version 1.
+pub fn save(path: &str, grid: &Grid, session: &Session) -> Result<(), Error> {
+    let mut file = File::create(path).map_err(|error| Error::File(path.to_string(), error))?;
+    file.write_all(to_string(grid, session).as_bytes())
+        .map_err(|error| Error::File(path.to_string(), error))
+}
+
+pub fn to_string(grid: &Grid, session: &Session) -> String {
+    let mut content = format!("sudoku {}\n", VERSION);
+    content += &format!(
+        "cursor {} {}\n",
+        session.current_position.0, session.current_position.1
+    );
+    content += &format!("mode {}\n", session.mode);
+    content += &format!("mark {}\n", session.mark_selected_color.name());
+    content += &format!("time {}\n", session.time);
+
+    for y in 0..9 {
+        for x in 0..9 {
+            let cell = &grid.cells[y][x];
+            if cell.value != 0 {
+                content += &cell.value.to_string();
+                content += "-";
+                if cell.initial {
+                    content += "i";
                 }
-                file.write_all("\n".as_bytes()).expect("cannot save sudoku");
             } else {
-                file.write_all("0-".as_bytes()).expect("cannot save sudoku");
-                let mut i = 0;
-                loop {
-                    if grid.cells[y][x].options.values[i].valid {
-                        file.write_all((i + 1).to_string().as_bytes())
-                            .expect("cannot save sudoku");
-                    }
-
-                    i += 1;
-                    if i == 9 {
-                        break;
+                content += "0-";
+                for i in 0..9 {
+                    if cell.options.values[i].valid {
+                        content += &(i + 1).to_string();
                     }
                 }
-                file.write_all("\n".as_bytes()).expect("cannot save sudoku");
             }
 
-            x += 1;
-            if x == 9 {
-                break;
+            if !cell.has_default_colors() {
+                content += &format!(" bg={} fg={}", cell.bg.name(), cell.fg.name());
             }
-        }
-        y += 1;
-        if y == 9 {
-            break;
+            if let Some(paint) = cell.paint {
+                content += &format!(" paint={}", paint.name());
+            }
+            for i in 0..9 {
+                let option = &cell.options.values[i];
+                if !option.has_default_colors() {
+                    content += &format!(" {}={}/{}", i + 1, option.bg.name(), option.fg.name());
+                }
+            }
+            content += "\n";
         }
     }
+    content
 }
 
-pub fn load(path: &String) -> Grid {
+pub fn load(path: &str) -> Result<(Grid, Session), Error> {
+    let content = fs::read_to_string(path).map_err(|error| Error::File(path.to_string(), error))?;
+    parse(&content).map_err(|error| error.in_file(path))
+}
+
+pub fn parse(content: &str) -> Result<(Grid, Session), Error> {
     let mut grid = Grid::default();
-    let reader = BufReader::new(File::open(path).expect("file read failed"));
+    let mut session = Session::default();
 
     let mut cell_index = 0;
-    for _line in reader.lines() {
-        let line = _line.unwrap();
-        let mut parts = line.split("-");
+    let mut line_number = 0;
+    for line in content.lines() {
+        line_number += 1;
+        let words = words(line);
+        let (column, first) = match words.first() {
+            Some(word) => *word,
+            None => continue,
+        };
+        let error = |column: usize, message: &str| {
+            Err(Error::parse(line_number, column, message.to_string()))
+        };
 
-        let left = parts.next().unwrap();
-        let right = parts.next().unwrap();
+        // header and session lines
+        match first {
+            "sudoku" => {
+                if words.get(1).map(|(_, version)| version.parse::<usize>()) != Some(Ok(VERSION)) {
+                    return error(column, "unsupported save file version");
+                }
+                continue;
+            }
+            "cursor" => {
+                let mut position = [0; 2];
+                for i in 0..2 {
+                    match words
+                        .get(i + 1)
+                        .map(|(at, word)| (at, word.parse::<usize>()))
+                    {
+                        Some((_, Ok(value))) if value < 9 => position[i] = value,
+                        Some((at, _)) => return error(*at, "expected a number from 0 to 8"),
+                        None => return error(column, "expected a row and a column"),
+                    }
+                }
+                session.current_position = (position[0], position[1]);
+                continue;
+            }
+            "mode" => {
+                match words.get(1) {
+                    Some((_, mode)) => session.mode = mode.to_string(),
+                    None => return error(column, "expected a mode"),
+                }
+                continue;
+            }
+            "mark" => {
+                match words.get(1) {
+                    Some((at, name)) => match Slot::from(name) {
+                        Some(color) => session.mark_selected_color = color,
+                        None => return error(*at, "unknown color"),
+                    },
+                    None => return error(column, "expected a color"),
+                }
+                continue;
+            }
+            "time" => {
+                match words.get(1).map(|(at, word)| (at, word.parse::<u64>())) {
+                    Some((_, Ok(seconds))) => session.time = seconds,
+                    Some((at, _)) => return error(*at, "expected a number of seconds"),
+                    None => return error(column, "expected a number of seconds"),
+                }
+                continue;
+            }
+            _ => {}
+        }
 
-        let column = cell_index % 9;
-        let row = (cell_index - column) / 9;
+        if cell_index == 81 {
+            return error(column, "expected 81 cells, found more");
+        }
 
-        if left == "0" {
-            for char in right.to_string().chars() {
-                let value = char.to_digit(10).unwrap() as usize;
-                grid.cells[row][column].options.values[value - 1].valid = true;
+        let (left, right) = match first.split_once('-') {
+            Some(parts) => parts,
+            None => return error(column, "expected `value-i` or `0-notes`"),
+        };
+
+        let x = cell_index % 9;
+        let y = (cell_index - x) / 9;
+        let cell = &mut grid.cells[y][x];
+
+        match left.parse::<usize>() {
+            Ok(0) => {
+                for (index, char) in right.chars().enumerate() {
+                    match char.to_digit(10) {
+                        Some(value) if value != 0 => {
+                            cell.options.values[value as usize - 1].valid = true
+                        }
+                        _ => return error(column + left.len() + 1 + index, "expected a note 1-9"),
+                    }
+                }
             }
-        } else {
-            let value: usize = left.parse().unwrap();
-            grid.cells[row][column].value = value;
-            if right == "i" {
-                grid.cells[row][column].initial = true;
+            Ok(value) if value <= 9 => {
+                cell.value = value;
+                match right {
+                    "i" => cell.initial = true,
+                    "" => {}
+                    _ => return error(column + left.len() + 1, "expected `i` or nothing"),
+                }
+            }
+            _ => return error(column, "expected a value 0-9"),
+        }
+
+        // colors, only present from version 2 on
+        for (at, word) in &words[1..] {
+            let (key, value) = match word.split_once('=') {
+                Some(parts) => parts,
+                None => return error(*at, "expected `key=color`"),
+            };
+            let value_at = at + key.len() + 1;
+            match key {
+                "paint" => match Slot::from(value) {
+                    Some(color) => cell.paint = Some(color),
+                    None => return error(value_at, "unknown color"),
+                },
+                "bg" | "fg" => {
+                    let color = match Slot::from(value) {
+                        Some(color) => color,
+                        None => return error(value_at, "unknown color"),
+                    };
+                    if key == "bg" {
+                        cell.bg = color;
+                    } else {
+                        cell.fg = color;
+                    }
+                }
+                _ => {
+                    let option = match key.parse::<usize>() {
+                        Ok(option) if (1..10).contains(&option) => option,
+                        _ => return error(*at, "expected `bg`, `fg`, `paint` or a note 1-9"),
+                    };
+                    let colors = value
+                        .split_once('/')
+                        .map(|(bg, fg)| (Slot::from(bg), Slot::from(fg)));
+                    match colors {
+                        Some((Some(bg), Some(fg))) => {
+                            cell.options.values[option - 1].bg = bg;
+                            cell.options.values[option - 1].fg = fg;
+                        }
+                        _ => return error(value_at, "expected `background/foreground` colors"),
+                    }
+                }
             }
         }
 
         cell_index += 1;
-        if cell_index == 81 {
-            break;
+    }
+
+    if cell_index != 81 {
+        return Err(Error::parse(
+            line_number + 1,
+            1,
+            format!("expected 81 cells, found {}", cell_index),
+        ));
+    }
+
+    Ok((grid, session))
+}
+
+// words of a line with the column each one starts at
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start: Option<usize> = None;
+    for (index, char) in line.char_indices() {
+        match (char.is_whitespace(), start) {
+            (true, Some(from)) => {
+                words.push((from + 1, &line[from..index]));
+                start = None;
+            }
+            (false, None) => start = Some(index),
+            _ => {}
+        }
+    }
+    if let Some(from) = start {
+        words.push((from + 1, &line[from..]));
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cells of version 1 files, one line each, without header or colors
+    fn version_1() -> String {
+        let mut content = String::from("5-i\n0-12\n7-\n");
+        for _ in 3..81 {
+            content += "0-\n";
         }
+        content
+    }
+
+    #[test]
+    fn version_1_files() {
+        let (grid, session) = parse(&version_1()).unwrap();
+        assert_eq!(grid.cells[0][0].value, 5);
+        assert!(grid.cells[0][0].initial);
+        assert!(grid.cells[0][1].options.values[0].valid);
+        assert!(grid.cells[0][1].options.values[1].valid);
+        assert!(!grid.cells[0][1].options.values[2].valid);
+        assert_eq!(grid.cells[0][2].value, 7);
+        assert!(!grid.cells[0][2].initial);
+        assert!(grid.cells[0][2].has_default_colors());
+        assert_eq!(session.current_position, (0, 0));
+        assert_eq!(session.time, 0);
     }
 
-    grid
+    #[test]
+    fn round_trip() {
+        let mut grid = Grid::default();
+        grid.cells[0][0].value = 5;
+        grid.cells[0][0].initial = true;
+        grid.cells[0][0].bg = Slot::Blue;
+        grid.cells[0][0].fg = Slot::Black;
+        grid.cells[4][7].add_option(1);
+        grid.cells[4][7].add_option(9);
+        grid.cells[4][7].paint = Some(Slot::Green);
+        grid.cells[4][7].options.values[8].bg = Slot::Purple;
+        grid.cells[4][7].options.values[8].fg = Slot::Black;
+        grid.cells[8][8].value = 3;
+        let session = Session {
+            current_position: (4, 7),
+            mode: String::from("note"),
+            mark_selected_color: Slot::Red,
+            time: 754,
+        };
+
+        let content = to_string(&grid, &session);
+        assert!(content.starts_with("sudoku 2\ncursor 4 7\nmode note\nmark red\ntime 754\n"));
+        let (parsed, restored) = parse(&content).unwrap();
+        assert!(parsed == grid);
+        assert_eq!(restored.current_position, (4, 7));
+        assert_eq!(restored.mode, "note");
+        assert_eq!(restored.mark_selected_color, Slot::Red);
+        assert_eq!(restored.time, 754);
+        assert_eq!(to_string(&parsed, &restored), content);
+    }
+
+    #[test]
+    fn errors() {
+        let error = parse("sudoku 9\n").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: unsupported save file version"
+        );
+        let error = parse(&version_1().replacen("0-12", "0-1x", 1))
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "line 2, column 4: expected a note 1-9");
+        let error = parse("5-i\n").err().unwrap();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: expected 81 cells, found 1"
+        );
+    }
 }
@@ -1,6 +1,71 @@
+use crate::{config, error::Error};
+use std::env;
 use tui::style::Color;
 
+pub const FILE: &str = "themes.toml";
+
+// What a color is used for. The model stores slots and the current theme
+// turns them into colors when drawing, so a theme switch or a saved board
+// keeps "mark color red" rather than some RGB value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Slot {
+    White,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Purple,
+    Cyan,
+    Orange,
+    DarkGrey,
+    Grey,
+    LightGrey,
+}
+
+impl Slot {
+    // in the order of `Theme::colors`
+    pub const ALL: [Slot; 12] = [
+        Slot::White,
+        Slot::Black,
+        Slot::Red,
+        Slot::Green,
+        Slot::Yellow,
+        Slot::Blue,
+        Slot::Purple,
+        Slot::Cyan,
+        Slot::Orange,
+        Slot::DarkGrey,
+        Slot::Grey,
+        Slot::LightGrey,
+    ];
+
+    // name in save and theme files
+    pub fn name(&self) -> &'static str {
+        match self {
+            Slot::White => "white",
+            Slot::Black => "black",
+            Slot::Red => "red",
+            Slot::Green => "green",
+            Slot::Yellow => "yellow",
+            Slot::Blue => "blue",
+            Slot::Purple => "purple",
+            Slot::Cyan => "cyan",
+            Slot::Orange => "orange",
+            Slot::DarkGrey => "dark_grey",
+            Slot::Grey => "grey",
+            Slot::LightGrey => "light_grey",
+        }
+    }
+
+    pub fn from(name: &str) -> Option<Slot> {
+        Slot::ALL.into_iter().find(|slot| slot.name() == name)
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct Theme {
+    pub name: String,
     pub white: Color,
     pub black: Color,
     pub red: Color,
@@ -9,6 +74,7 @@ pub struct Theme {
     pub blue: Color,
     pub purple: Color,
     pub cyan: Color,
+    pub orange: Color,
     pub dark_grey: Color,
     pub grey: Color,
     pub light_grey: Color,
@@ -17,6 +83,7 @@ pub struct Theme {
 impl Default for Theme {
     fn default() -> Self {
         Self {
+            name: String::from("dark"),
             white: Color::Rgb(200, 200, 200),
             black: Color::Rgb(31, 35, 53),
             red: Color::Rgb(247, 118, 142),
@@ -25,9 +92,250 @@ impl Default for Theme {
             blue: Color::Rgb(122, 162, 247),
             purple: Color::Rgb(187, 154, 247),
             cyan: Color::Rgb(127, 207, 255),
+            orange: Color::Rgb(255, 158, 100),
             dark_grey: Color::Rgb(65, 72, 104),
             grey: Color::Rgb(121, 130, 169),
             light_grey: Color::Rgb(169, 177, 214),
         }
     }
 }
+
+impl Theme {
+    // Dark text on a light board. `white` is the text and `dark_grey` the
+    // background of cells, as in the dark theme.
+    pub fn light() -> Self {
+        Self {
+            name: String::from("light"),
+            white: Color::Rgb(55, 96, 191),
+            black: Color::Rgb(233, 233, 237),
+            red: Color::Rgb(245, 42, 101),
+            green: Color::Rgb(88, 117, 57),
+            yellow: Color::Rgb(140, 108, 62),
+            blue: Color::Rgb(46, 125, 233),
+            purple: Color::Rgb(152, 84, 241),
+            cyan: Color::Rgb(0, 113, 151),
+            orange: Color::Rgb(177, 92, 0),
+            dark_grey: Color::Rgb(208, 213, 227),
+            grey: Color::Rgb(97, 114, 176),
+            light_grey: Color::Rgb(161, 166, 197),
+        }
+    }
+
+    // Okabe-Ito colors, told apart with the common kinds of color blindness
+    pub fn colorblind() -> Self {
+        Self {
+            name: String::from("colorblind"),
+            red: Color::Rgb(213, 94, 0),
+            green: Color::Rgb(0, 158, 115),
+            yellow: Color::Rgb(240, 228, 66),
+            blue: Color::Rgb(0, 114, 178),
+            purple: Color::Rgb(204, 121, 167),
+            cyan: Color::Rgb(86, 180, 233),
+            orange: Color::Rgb(230, 159, 0),
+            ..Theme::default()
+        }
+    }
+
+    // the 16 colors every terminal has, for those without true color
+    pub fn ansi() -> Self {
+        Self {
+            name: String::from("ansi"),
+            white: Color::White,
+            black: Color::Black,
+            red: Color::Red,
+            green: Color::Green,
+            yellow: Color::Yellow,
+            blue: Color::Blue,
+            purple: Color::Magenta,
+            cyan: Color::Cyan,
+            orange: Color::LightRed,
+            dark_grey: Color::Reset,
+            grey: Color::DarkGray,
+            light_grey: Color::Gray,
+        }
+    }
+
+    pub fn colors(&self) -> [Color; 12] {
+        [
+            self.white,
+            self.black,
+            self.red,
+            self.green,
+            self.yellow,
+            self.blue,
+            self.purple,
+            self.cyan,
+            self.orange,
+            self.dark_grey,
+            self.grey,
+            self.light_grey,
+        ]
+    }
+
+    fn color_mut(&mut self, slot: Slot) -> &mut Color {
+        match slot {
+            Slot::White => &mut self.white,
+            Slot::Black => &mut self.black,
+            Slot::Red => &mut self.red,
+            Slot::Green => &mut self.green,
+            Slot::Yellow => &mut self.yellow,
+            Slot::Blue => &mut self.blue,
+            Slot::Purple => &mut self.purple,
+            Slot::Cyan => &mut self.cyan,
+            Slot::Orange => &mut self.orange,
+            Slot::DarkGrey => &mut self.dark_grey,
+            Slot::Grey => &mut self.grey,
+            Slot::LightGrey => &mut self.light_grey,
+        }
+    }
+
+    pub fn color(&self, slot: Slot) -> Color {
+        self.colors()[slot as usize]
+    }
+}
+
+// Built-in themes followed by the ones from the config file, and the index of
+// the one to start with. The file can define themes based on others and pick
+// the starting one:
+//
+//     theme = "mine"
+//
+//     [themes.mine]
+//     base = "light"
+//     red = "#ff0000"
+//     cyan = "lightcyan"
+//
+// Without a choice, terminals that do not report true color get `ansi`.
+pub fn load() -> Result<(Vec<Theme>, usize), Error> {
+    let mut themes = builtin();
+    let true_color = matches!(
+        env::var("COLORTERM").as_deref(),
+        Ok("truecolor") | Ok("24bit")
+    );
+    let mut start = String::from(if true_color { "dark" } else { "ansi" });
+
+    let (content, table) = match config::read(FILE)? {
+        Some(config) => config,
+        None => {
+            let start = index(&themes, &start).unwrap_or(0);
+            return Ok((themes, start));
+        }
+    };
+    let error = |key: &str, message: String| Err(config::error(FILE, &content, key, message));
+
+    match table.get("themes") {
+        None => {}
+        Some(toml::Value::Table(defined)) => {
+            for (name, value) in defined {
+                let fields = match value {
+                    toml::Value::Table(fields) => fields,
+                    _ => return error(name, String::from("expected a [themes.name] table")),
+                };
+                let mut theme = match fields.get("base") {
+                    None => Theme::default(),
+                    Some(toml::Value::String(base)) => match index(&themes, base) {
+                        Some(base) => themes[base].clone(),
+                        None => return error("base", format!("unknown theme `{}`", base)),
+                    },
+                    Some(_) => return error("base", String::from("expected a theme name")),
+                };
+                theme.name = name.clone();
+                for (field, value) in fields {
+                    if field == "base" {
+                        continue;
+                    }
+                    let color = match value {
+                        toml::Value::String(color) => parse_color(color),
+                        toml::Value::Integer(index) => {
+                            u8::try_from(*index).ok().map(Color::Indexed)
+                        }
+                        _ => None,
+                    };
+                    match (Slot::from(field), color) {
+                        (Some(slot), Some(color)) => *theme.color_mut(slot) = color,
+                        (None, _) => {
+                            let names: Vec<&str> = Slot::ALL.iter().map(Slot::name).collect();
+                            return error(
+                                field,
+                                format!(
+                                    "unknown color `{}`, expected one of {}",
+                                    field,
+                                    names.join(", ")
+                                ),
+                            );
+                        }
+                        (_, None) => return error(field, String::from("expected a color")),
+                    }
+                }
+                match index(&themes, name) {
+                    Some(existing) => themes[existing] = theme,
+                    None => themes.push(theme),
+                }
+            }
+        }
+        Some(_) => return error("themes", String::from("expected [themes.name] tables")),
+    }
+
+    match table.get("theme") {
+        None => {}
+        Some(toml::Value::String(name)) => start = name.clone(),
+        Some(_) => return error("theme", String::from("expected a theme name")),
+    }
+    match index(&themes, &start) {
+        Some(start) => Ok((themes, start)),
+        None => error("theme", format!("unknown theme `{}`", start)),
+    }
+}
+
+pub fn builtin() -> Vec<Theme> {
+    vec![
+        Theme::default(),
+        Theme::light(),
+        Theme::colorblind(),
+        Theme::ansi(),
+    ]
+}
+
+fn index(themes: &[Theme], name: &str) -> Option<usize> {
+    themes.iter().position(|theme| theme.name == name)
+}
+
+const COLOR_NAMES: [(Color, &str); 17] = [
+    (Color::Reset, "reset"),
+    (Color::Black, "black"),
+    (Color::Red, "red"),
+    (Color::Green, "green"),
+    (Color::Yellow, "yellow"),
+    (Color::Blue, "blue"),
+    (Color::Magenta, "magenta"),
+    (Color::Cyan, "cyan"),
+    (Color::Gray, "gray"),
+    (Color::DarkGray, "darkgray"),
+    (Color::LightRed, "lightred"),
+    (Color::LightGreen, "lightgreen"),
+    (Color::LightYellow, "lightyellow"),
+    (Color::LightBlue, "lightblue"),
+    (Color::LightMagenta, "lightmagenta"),
+    (Color::LightCyan, "lightcyan"),
+    (Color::White, "white"),
+];
+
+// `#rrggbb`, a color index or one of the names above
+pub fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+        return Some(Color::Rgb(r, g, b));
+    }
+    if let Ok(index) = name.parse() {
+        return Some(Color::Indexed(index));
+    }
+    COLOR_NAMES
+        .iter()
+        .find(|(_, named)| *named == name)
+        .map(|(color, _)| *color)
+}
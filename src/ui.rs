@@ -1,39 +1,191 @@
-use std::{borrow::BorrowMut, io::Stdout};
+use crossterm::event::{Event as TermEvent, KeyCode, MouseButton, MouseEvent, MouseEventKind};
+use std::{
+    borrow::BorrowMut,
+    io::{self, Stdout},
+};
 
-use crossterm::event::KeyModifiers;
-use termion::event::Key;
 use tui::{
     backend::CrosstermBackend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::Span,
+    style::{Modifier, Style},
+    text::{Span, Spans},
     widgets::{Block, BorderType, Borders, Paragraph},
     Terminal,
 };
 
 use crate::{
-    board::{Board, BoardWidget},
-    events::{Event, Events},
+    events::{Event, Events, TICK_RATE_MS},
+    keymap::{key_name, Action, Key, Keymap},
+};
+use sudoku_rust::{
+    board::Board,
+    generator::{self, Difficulty},
     grid::Grid,
-    sync::save,
-    theme::Theme,
+    hint, rater,
+    solver::{self, Solution},
+    sync::{save, Session},
+    theme::{Slot, Theme},
+    widget::{BoardWidget, Geometry},
 };
 
-#[derive(PartialEq)]
+// messages disappear after ten seconds of ticks
+const MESSAGE_TICKS: usize = 10_000 / TICK_RATE_MS as usize;
+
+#[derive(PartialEq, Clone, Copy)]
 enum Mode {
     Insert,
     Note,
     Mark,
     Highlight,
     HighlightOnly,
+    Select,
+    Paint,
     Features,
     MarkColorSelect,
+    Generate,
+    SaveAs,
+}
+
+impl Mode {
+    fn name(&self) -> &'static str {
+        match self {
+            Mode::Insert => "insert",
+            Mode::Note => "note",
+            Mode::Mark => "mark",
+            Mode::Highlight => "highlight",
+            Mode::HighlightOnly => "highlight-only",
+            Mode::Select => "select",
+            Mode::Paint => "paint",
+            Mode::Features => "features",
+            Mode::MarkColorSelect => "mark-color-select",
+            Mode::Generate => "generate",
+            Mode::SaveAs => "save-as",
+        }
+    }
+
+    fn from(name: &str) -> Self {
+        match name {
+            "note" => Mode::Note,
+            "mark" => Mode::Mark,
+            "highlight" => Mode::Highlight,
+            "highlight-only" => Mode::HighlightOnly,
+            "select" => Mode::Select,
+            "paint" => Mode::Paint,
+            "mark-color-select" => Mode::MarkColorSelect,
+            _ => Mode::Insert,
+        }
+    }
 }
 
+// handles a number key, 0 to 9
+type Press = fn(&mut UI, usize);
+type Choose = fn(&mut UI);
+
+// What the number keys do in each mode, read by both the key handler and the
+// help overlay. Save as is left out, typing a file name takes every key.
+const NUMBERS: [(Mode, &str, Press); 10] = [
+    (Mode::Insert, "insert value, 0 clears", |ui, value| {
+        ui.insert(value)
+    }),
+    (Mode::Note, "toggle note", |ui, value| {
+        if value > 0 {
+            ui.board.toggle_option(value)
+        }
+    }),
+    (Mode::Mark, "mark note", |ui, value| {
+        if value > 0 {
+            ui.board.mark(value, ui.mark_selected_color)
+        }
+    }),
+    (Mode::MarkColorSelect, "pick mark color", |ui, value| {
+        if value > 0 {
+            ui.mark_selected_color = mark_colors()[value - 1];
+            ui.mode = Mode::Mark;
+        }
+    }),
+    (Mode::HighlightOnly, "highlight value", |ui, value| {
+        if value > 0 {
+            ui.highlight(value, true)
+        }
+    }),
+    (Mode::Highlight, "add highlight", |ui, value| {
+        if value > 0 {
+            ui.highlight(value, false)
+        }
+    }),
+    (Mode::Select, "select cells with note", |ui, value| {
+        if value > 0 {
+            let count = ui.board.select_candidate(value);
+            ui.show_message(format!("{} cells with note {}", count, value));
+        }
+    }),
+    (Mode::Paint, "0 clears paint", |ui, value| {
+        if value == 0 {
+            ui.board.paint(None)
+        }
+    }),
+    (Mode::Features, "pick menu entry", |ui, value| {
+        if let Some((_, choose)) = value.checked_sub(1).and_then(|index| MENU.get(index)) {
+            ui.mode = Mode::Insert;
+            choose(ui);
+        }
+    }),
+    (Mode::Generate, "pick difficulty", |ui, value| {
+        if let Some(difficulty) = value
+            .checked_sub(1)
+            .and_then(|index| Difficulty::ALL.get(index))
+        {
+            ui.new_puzzle(*difficulty)
+        }
+    }),
+];
+
+// Entries of the features menu, numbered from 1 in this order. Entries pick
+// their own mode afterwards, insert unless they say otherwise.
+const MENU: [(&str, Choose); 8] = [
+    ("Auto-fill", |ui| ui.board.autofill()),
+    ("Hint", UI::hint),
+    ("Save", UI::save),
+    ("Reveal cell", |ui| ui.solve(false)),
+    ("Solve", |ui| ui.solve(true)),
+    ("Toggle strict mode", UI::toggle_strict),
+    ("New puzzle", |ui| ui.mode = Mode::Generate),
+    ("Save as", UI::open_save_as),
+];
+
 pub struct UI {
     board: Board,
     mode: Mode,
-    mark_selected_color: Color,
+    // mode to save while the menu is open
+    previous_mode: Mode,
+    mark_selected_color: Slot,
+    // shown in the status bar until replaced, colors are reset or it expires
+    message: String,
+    // ticks left before the message is cleared
+    message_ticks: usize,
+    // difficulty of the puzzle, empty if it has no unique solution
+    rating: String,
+    // file the board was loaded from or last saved to
+    path: Option<String>,
+    // text typed into the save as prompt
+    input: String,
+    // where and how big the board was last drawn, for mouse clicks
+    board_area: Rect,
+    geometry: Geometry,
+    // last highlighted value, scrolling moves on from it
+    highlighted: usize,
+    keymap: Keymap,
+    themes: Vec<Theme>,
+    // index of the current theme in `themes`
+    theme: usize,
+    // help overlay is shown instead of the board
+    help: bool,
+    // ticks counted while the board was shown, the time spent on the puzzle
+    ticks: u64,
+    // board hidden and timer stopped until a key is pressed
+    paused: bool,
+    // the terminal window has focus, for terminals that report it
+    focused: bool,
 }
 
 impl Default for UI {
@@ -41,7 +193,23 @@ impl Default for UI {
         Self {
             board: Board::default(),
             mode: Mode::Insert,
-            mark_selected_color: Theme::default().purple,
+            previous_mode: Mode::Insert,
+            mark_selected_color: Slot::Purple,
+            message: String::new(),
+            message_ticks: 0,
+            rating: String::new(),
+            path: None,
+            input: String::new(),
+            board_area: Rect::default(),
+            geometry: Geometry::Full,
+            highlighted: 0,
+            keymap: Keymap::default(),
+            themes: vec![Theme::default()],
+            theme: 0,
+            help: false,
+            ticks: 0,
+            paused: false,
+            focused: true,
         }
     }
 }
@@ -49,265 +217,702 @@ impl Default for UI {
 impl UI {
     pub fn from(grid: Grid) -> Self {
         Self {
+            rating: rating(&grid),
             board: Board::from(grid),
             mode: Mode::Insert,
-            mark_selected_color: Theme::default().purple,
+            previous_mode: Mode::Insert,
+            mark_selected_color: Slot::Purple,
+            message: String::new(),
+            message_ticks: 0,
+            path: None,
+            input: String::new(),
+            board_area: Rect::default(),
+            geometry: Geometry::Full,
+            highlighted: 0,
+            keymap: Keymap::default(),
+            themes: vec![Theme::default()],
+            theme: 0,
+            help: false,
+            ticks: 0,
+            paused: false,
+            focused: true,
+        }
+    }
+
+    pub fn set_keymap(&mut self, keymap: Keymap) {
+        self.keymap = keymap;
+    }
+
+    pub fn set_themes(&mut self, themes: Vec<Theme>, current: usize) {
+        self.themes = themes;
+        self.theme = current;
+    }
+
+    // Switches to the next theme. The board stores slots, so marks,
+    // highlights and paint simply take the colors of the new theme.
+    fn next_theme(&mut self) {
+        self.theme = (self.theme + 1) % self.themes.len();
+        self.show_message(format!("{} theme", self.themes[self.theme].name));
+    }
+
+    pub fn set_path(&mut self, path: String) {
+        self.path = Some(path);
+    }
+
+    pub fn restore(&mut self, session: Session) {
+        self.board.current_position = session.current_position;
+        self.mode = Mode::from(&session.mode);
+        self.mark_selected_color = session.mark_selected_color;
+        // save files may hold any number of seconds
+        self.ticks = session.time.saturating_mul(1000) / TICK_RATE_MS;
+    }
+
+    fn session(&self) -> Session {
+        Session {
+            current_position: self.board.current_position,
+            mode: String::from(self.previous_mode.name()),
+            mark_selected_color: self.mark_selected_color,
+            time: self.seconds(),
         }
     }
 
+    fn seconds(&self) -> u64 {
+        self.ticks.saturating_mul(TICK_RATE_MS) / 1000
+    }
+
+    // The clock only runs while the board can be seen and is not solved yet,
+    // menus, help, the pause screen and switching to another window stop it.
+    fn timing(&self) -> bool {
+        !self.paused
+            && self.focused
+            && !self.help
+            && !matches!(self.mode, Mode::Features | Mode::Generate | Mode::SaveAs)
+            && !self.board.grid.is_solved()
+    }
+
+    pub fn show_message(&mut self, message: String) {
+        self.message = message;
+        self.message_ticks = MESSAGE_TICKS;
+    }
+
+    // mode, mark color, cursor, file and message, shown under the board
+    fn status(&self) -> Spans<'_> {
+        let theme = &self.themes[self.theme];
+        let separator = || Span::styled(" | ", Style::default().fg(theme.grey));
+        let (y, x) = self.board.current_position;
+
+        let mut spans = vec![
+            Span::styled(
+                format!(" {} ", self.mode.name().to_uppercase()),
+                Style::default()
+                    .fg(theme.black)
+                    .bg(theme.green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" mark "),
+            Span::styled(
+                "  ",
+                Style::default().bg(theme.color(self.mark_selected_color)),
+            ),
+            separator(),
+            Span::raw(hint::position((y, x))),
+        ];
+        if !self.board.selection.is_empty() {
+            spans.push(Span::raw(format!(
+                " ({} selected)",
+                self.board.selection.len()
+            )));
+        }
+        if self.board.strict {
+            spans.push(separator());
+            spans.push(Span::raw("strict"));
+        }
+        spans.push(separator());
+        spans.push(Span::raw(
+            self.path
+                .clone()
+                .unwrap_or_else(|| String::from("[no file]")),
+        ));
+        if !self.message.is_empty() {
+            spans.push(separator());
+            spans.push(Span::styled(
+                self.message.clone(),
+                Style::default().fg(theme.yellow),
+            ));
+        }
+        Spans::from(spans)
+    }
+
+    fn save(&mut self) {
+        match self.path.clone() {
+            Some(path) => {
+                self.show_message(match save(&path, &self.board.grid, &self.session()) {
+                    Ok(()) => format!("saved to {}", path),
+                    Err(error) => format!("save failed: {}", error),
+                });
+                self.mode = Mode::Insert;
+            }
+            None => self.open_save_as(),
+        }
+    }
+
+    fn hint(&mut self) {
+        match hint::find(&self.board.grid) {
+            Some(hint) => {
+                self.board.show_hint(&hint);
+                self.show_message(hint.describe());
+            }
+            None => self.show_message(String::from("no hint found")),
+        }
+    }
+
+    // fills the current cell, or every cell, with the solution if there is one
+    fn solve(&mut self, all: bool) {
+        match solver::solve(&self.board.grid) {
+            Solution::Unique(solution) if all => self.board.fill(&solution),
+            Solution::Unique(solution) => self.board.reveal(&solution),
+            Solution::Multiple => self.show_message(String::from("puzzle has multiple solutions")),
+            Solution::None => self.show_message(String::from("puzzle has no solution")),
+        }
+    }
+
+    fn toggle_strict(&mut self) {
+        self.board.strict = !self.board.strict;
+        self.show_message(if self.board.strict {
+            String::from("strict mode on")
+        } else {
+            String::from("strict mode off")
+        });
+    }
+
+    fn open_save_as(&mut self) {
+        self.input = self.path.clone().unwrap_or_default();
+        self.mode = Mode::SaveAs;
+    }
+
+    fn save_as_input(&mut self, key: Key) {
+        match key.code {
+            KeyCode::Enter => {
+                let mut path = self.input.trim().to_string();
+                if path.is_empty() {
+                    return;
+                }
+                if !path.ends_with(".sudoku") {
+                    path.push_str(".sudoku");
+                }
+                self.path = Some(path);
+                self.save();
+            }
+            KeyCode::Char(char) if !key.is_chord() => self.input.push(char),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Esc => self.mode = Mode::Insert,
+            _ => {}
+        }
+    }
+
+    fn new_puzzle(&mut self, difficulty: Difficulty) {
+        let seed = generator::random_seed();
+        let (grid, reached) = generator::generate(difficulty, seed);
+        self.rating = rating(&grid);
+        self.board = Board::from(grid);
+        self.path = None;
+        self.ticks = 0;
+        self.show_message(generator::describe(difficulty, reached, seed));
+        self.mode = Mode::Insert;
+    }
+
+    fn insert(&mut self, value: usize) {
+        if let Some(&(y, x)) = self.board.set_value(value).first() {
+            let clashes: Vec<String> = self
+                .board
+                .grid
+                .clashes(y, x, value)
+                .into_iter()
+                .map(hint::position)
+                .collect();
+            self.show_message(format!("{} clashes with {}", value, clashes.join(", ")));
+        }
+    }
+
+    fn perform(&mut self, action: Action) {
+        match action {
+            Action::MoveUp => self.board.move_up(),
+            Action::MoveDown => self.board.move_down(),
+            Action::MoveLeft => self.board.move_left(),
+            Action::MoveRight => self.board.move_right(),
+            Action::JumpUp => {
+                self.board.move_up();
+                self.board.move_up();
+                self.board.move_up();
+            }
+            Action::JumpDown => {
+                self.board.move_down();
+                self.board.move_down();
+                self.board.move_down();
+            }
+            Action::JumpLeft => {
+                self.board.move_left();
+                self.board.move_left();
+                self.board.move_left();
+            }
+            Action::JumpRight => {
+                self.board.move_right();
+                self.board.move_right();
+                self.board.move_right();
+            }
+            Action::ExtendUp => self.extend(8, 0),
+            Action::ExtendDown => self.extend(1, 0),
+            Action::ExtendLeft => self.extend(0, 8),
+            Action::ExtendRight => self.extend(0, 1),
+            Action::InsertMode => self.mode = Mode::Insert,
+            Action::NoteMode => self.mode = Mode::Note,
+            Action::MarkMode => self.mode = Mode::Mark,
+            Action::MarkColorMode => self.mode = Mode::MarkColorSelect,
+            Action::HighlightOnlyMode => self.mode = Mode::HighlightOnly,
+            Action::HighlightMode => self.mode = Mode::Highlight,
+            Action::SelectMode => self.mode = Mode::Select,
+            Action::PaintMode => self.mode = Mode::Paint,
+            Action::Paint => {
+                if self.mode == Mode::Paint {
+                    self.board.paint(Some(self.mark_selected_color));
+                }
+            }
+            Action::Unpaint => {
+                if self.mode == Mode::Paint {
+                    self.board.paint(None);
+                }
+            }
+            Action::ClearPaint => self.board.clear_paint(),
+            Action::ResetColors => {
+                self.board.reset_colors();
+                self.message.clear();
+            }
+            Action::NextTheme => self.next_theme(),
+            Action::Undo => {
+                if !self.board.undo() {
+                    self.show_message(String::from("nothing to undo"));
+                }
+            }
+            Action::Redo => {
+                if !self.board.redo() {
+                    self.show_message(String::from("nothing to redo"));
+                }
+            }
+            Action::Menu => {
+                if self.mode == Mode::Features {
+                    self.mode = Mode::Insert;
+                } else {
+                    self.previous_mode = self.mode;
+                    self.mode = Mode::Features;
+                }
+            }
+            Action::Cancel => match self.mode {
+                Mode::Features | Mode::Generate => self.mode = Mode::Insert,
+                _ => self.board.clear_selection(),
+            },
+            Action::Pause => self.paused = true,
+            Action::Help => self.help = !self.help,
+            // leaving the event loop is up to `run`
+            Action::Quit => {}
+        }
+    }
+
+    // Keys from the keymap on the left, what numbers do in each mode and the
+    // mouse on the right.
+    fn help_text(&self) -> (Vec<Spans<'_>>, Vec<Spans<'_>>) {
+        let theme = &self.themes[self.theme];
+        let key_style = Style::default().fg(theme.blue);
+        let heading = |text: &'static str| {
+            Spans::from(Span::styled(
+                text,
+                Style::default()
+                    .fg(theme.green)
+                    .add_modifier(Modifier::BOLD),
+            ))
+        };
+
+        let mut keys = vec![heading("Keys")];
+        for action in Action::ALL {
+            let names: Vec<String> = self.keymap.keys(action).into_iter().map(key_name).collect();
+            if names.is_empty() {
+                continue;
+            }
+            keys.push(Spans::from(vec![
+                Span::styled(format!("{:<18}", names.join(", ")), key_style),
+                Span::raw(action.description()),
+            ]));
+        }
+
+        let mut other = vec![heading("Numbers")];
+        for (mode, description, _) in NUMBERS {
+            other.push(Spans::from(vec![
+                Span::styled(format!("{:<10}", mode.name()), key_style),
+                Span::raw(description),
+            ]));
+        }
+        other.push(Spans::default());
+        other.push(heading("Mouse"));
+        for (button, description) in [
+            ("click", "select cell, use note"),
+            ("right", "place note as value"),
+            ("drag", "extend selection"),
+            ("scroll", "next value or color"),
+        ] {
+            other.push(Spans::from(vec![
+                Span::styled(format!("{:<10}", button), key_style),
+                Span::raw(description),
+            ]));
+        }
+        (keys, other)
+    }
+
+    // moves the cursor by the given rows and columns, wrapping around, and
+    // adds the cells on both ends to the selection
+    fn extend(&mut self, rows: usize, columns: usize) {
+        let (y, x) = self.board.current_position;
+        self.board
+            .extend_selection(((y + rows) % 9, (x + columns) % 9));
+    }
+
+    // Left click selects a cell and, on one of its candidates, toggles, marks
+    // or highlights it depending on the mode. Right click on a candidate
+    // places it. Scrolling steps through highlighted values or mark colors.
+    fn mouse(&mut self, event: MouseEvent) {
+        // the board under the help overlay is not where it was drawn
+        if self.help {
+            return;
+        }
+        match self.mode {
+            Mode::Features | Mode::Generate | Mode::SaveAs => return,
+            _ => {}
+        }
+
+        let (x, y) = (event.column, event.row);
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let (position, option) = match self.hit(x, y) {
+                    Some(hit) => hit,
+                    None => return,
+                };
+                self.board.clear_selection();
+                self.board.current_position = position;
+                if self.mode == Mode::Paint {
+                    self.board.paint(Some(self.mark_selected_color));
+                } else if let Some(value) = option {
+                    match self.mode {
+                        Mode::Note => self.board.toggle_option(value),
+                        Mode::Mark => self.board.mark(value, self.mark_selected_color),
+                        Mode::Highlight => self.highlight(value, false),
+                        Mode::HighlightOnly => self.highlight(value, true),
+                        _ => {}
+                    }
+                }
+            }
+            // dragging with the left button held
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((position, _)) = self.hit(x, y) {
+                    if position != self.board.current_position {
+                        self.board.extend_selection(position);
+                    }
+                }
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                if let Some((position, Some(value))) = self.hit(x, y) {
+                    self.board.current_position = position;
+                    self.insert(value);
+                }
+            }
+            kind @ (MouseEventKind::ScrollUp | MouseEventKind::ScrollDown) => {
+                // one back or one forward, wrapping around after nine
+                let step = if kind == MouseEventKind::ScrollUp {
+                    8
+                } else {
+                    1
+                };
+                match self.mode {
+                    Mode::Highlight | Mode::HighlightOnly => {
+                        let value = (self.highlighted + 8 + step) % 9 + 1;
+                        self.highlight(value, self.mode == Mode::HighlightOnly);
+                    }
+                    Mode::Mark | Mode::MarkColorSelect => {
+                        let colors = mark_colors();
+                        let index = colors
+                            .iter()
+                            .position(|color| *color == self.mark_selected_color)
+                            .unwrap_or(0);
+                        self.mark_selected_color = colors[(index + step) % 9];
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // cell and option under a mouse position
+    fn hit(&self, x: u16, y: u16) -> Option<((usize, usize), Option<usize>)> {
+        BoardWidget::hit(self.board_area, self.geometry, x, y)
+    }
+
+    fn highlight(&mut self, value: usize, only: bool) {
+        if only {
+            self.board.highlight_only(value);
+        } else {
+            self.board.highlight(value);
+        }
+        self.highlighted = value;
+    }
+
     pub fn run(
         &mut self,
-        mut terminal: Terminal<CrosstermBackend<Stdout>>,
+        terminal: &mut Terminal<CrosstermBackend<Stdout>>,
         events: Events,
-    ) -> Terminal<CrosstermBackend<Stdout>> {
+    ) -> Result<(), io::Error> {
         loop {
-            terminal
-                .draw(|frame| {
-                    let terminal_rect = frame.size();
+            terminal.draw(|frame| {
+                let terminal_rect = frame.size();
 
-                    // println!("{}, {}", terminal_rect.height, terminal_rect.width);
+                // println!("{}, {}", terminal_rect.height, terminal_rect.width);
 
-                    if terminal_rect.width < 80 || terminal_rect.height < 40 {
+                // smaller cells, or no options at all, when space is short
+                match Geometry::fit(terminal_rect) {
+                    Some(geometry) => self.geometry = geometry,
+                    None => {
                         let message = Paragraph::new("Window is too small\nPlease expand window")
                             .alignment(Alignment::Center);
                         frame.render_widget(message, terminal_rect);
 
-                        return ();
+                        return;
                     }
+                }
 
-                    let outer_block = Block::default()
-                        .borders(Borders::ALL)
-                        .title(Span::styled(
+                let theme = &self.themes[self.theme];
+                let outer_block = Block::default()
+                    .borders(Borders::ALL)
+                    .title(Spans::from(vec![
+                        Span::styled(
                             "sudoku",
                             Style::default()
-                                .fg(Theme::default().green)
+                                .fg(theme.green)
                                 .add_modifier(Modifier::BOLD),
-                        ))
-                        .border_type(BorderType::Rounded);
-                    frame.render_widget(outer_block, terminal_rect);
-
-                    if self.mode == Mode::Features {
-                        let menu = Paragraph::new(
-                            "Press button to select action:\n 1. Auto-fill \n 2. Hint \n 3. Save",
-                        )
+                        ),
+                        Span::styled(
+                            if self.rating.is_empty() {
+                                String::new()
+                            } else {
+                                format!(" {} ", self.rating)
+                            },
+                            Style::default().fg(theme.grey),
+                        ),
+                        Span::styled(
+                            format!(" {} ", clock(self.seconds())),
+                            Style::default().fg(if self.timing() {
+                                theme.white
+                            } else {
+                                theme.grey
+                            }),
+                        ),
+                    ]))
+                    .border_type(BorderType::Rounded);
+                frame.render_widget(outer_block, terminal_rect);
+
+                if self.help {
+                    let block = Block::default().title("Help").borders(Borders::ALL);
+                    let inner = block.inner(terminal_rect);
+                    frame.render_widget(block, terminal_rect);
+
+                    let columns = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Length(44), Constraint::Min(0)].as_ref())
+                        .split(inner);
+                    let (keys, other) = self.help_text();
+                    frame.render_widget(Paragraph::new(keys), columns[0]);
+                    frame.render_widget(Paragraph::new(other), columns[1]);
+                } else if self.paused {
+                    let inner = Rect {
+                        y: terminal_rect.y + terminal_rect.height / 2 - 1,
+                        height: 2,
+                        ..terminal_rect
+                    };
+                    let message = Paragraph::new("Paused\nPress any key to resume")
+                        .alignment(Alignment::Center);
+                    frame.render_widget(message, inner);
+                } else if self.mode == Mode::Features {
+                    let mut text = String::from("Press button to select action:");
+                    for (index, (name, _)) in MENU.iter().enumerate() {
+                        text += &format!("\n {}. {} ", index + 1, name);
+                    }
+                    let menu = Paragraph::new(text)
                         .block(Block::default().title("Paragraph").borders(Borders::ALL));
 
-                        frame.render_widget(menu, terminal_rect);
-
-                        // let menu = Paragraph::new("black")
-                        //     .style(Style::default().bg(Color::Rgb(255, 0, 0)));
-
-                        // let blocks = Layout::default()
-                        //     .direction(Direction::Horizontal)
-                        //     .constraints(
-                        //         [
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //             Constraint::Ratio(1, 9),
-                        //         ]
-                        //         .as_ref(),
-                        //     )
-                        //     .split(terminal_rect);
-
-                        // let colors = [
-                        //     Color::Rgb(31, 35, 53),
-                        //     Color::Rgb(247, 118, 142),
-                        //     Color::Rgb(115, 218, 202),
-                        //     Color::Rgb(224, 175, 104),
-                        //     Color::Rgb(122, 162, 247),
-                        //     Color::Rgb(187, 154, 247),
-                        //     Color::Rgb(127, 207, 255),
-                        //     Color::Rgb(121, 130, 169),
-                        //     Color::Rgb(169, 177, 214),
-                        // ];
-
-                        // for (index, rect) in blocks.into_iter().enumerate() {
-                        //     let menu =
-                        //         Paragraph::new("black").style(Style::default().bg(colors[index]));
-                        //     frame.render_widget(menu, rect)
-                        // }
-                    } else {
-                        let board_widget = BoardWidget {};
-                        frame.render_stateful_widget(
-                            board_widget,
-                            terminal_rect,
-                            self.board.borrow_mut(),
-                        )
+                    frame.render_widget(menu, terminal_rect);
+
+                    // let menu = Paragraph::new("black")
+                    //     .style(Style::default().bg(Color::Rgb(255, 0, 0)));
+
+                    // let blocks = Layout::default()
+                    //     .direction(Direction::Horizontal)
+                    //     .constraints(
+                    //         [
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //             Constraint::Ratio(1, 9),
+                    //         ]
+                    //         .as_ref(),
+                    //     )
+                    //     .split(terminal_rect);
+
+                    // let colors = [
+                    //     Color::Rgb(31, 35, 53),
+                    //     Color::Rgb(247, 118, 142),
+                    //     Color::Rgb(115, 218, 202),
+                    //     Color::Rgb(224, 175, 104),
+                    //     Color::Rgb(122, 162, 247),
+                    //     Color::Rgb(187, 154, 247),
+                    //     Color::Rgb(127, 207, 255),
+                    //     Color::Rgb(121, 130, 169),
+                    //     Color::Rgb(169, 177, 214),
+                    // ];
+
+                    // for (index, rect) in blocks.into_iter().enumerate() {
+                    //     let menu =
+                    //         Paragraph::new("black").style(Style::default().bg(colors[index]));
+                    //     frame.render_widget(menu, rect)
+                    // }
+                } else if self.mode == Mode::Generate {
+                    let mut text = String::from("Select difficulty:");
+                    for (index, difficulty) in Difficulty::ALL.iter().enumerate() {
+                        text += &format!("\n {}. {} ", index + 1, difficulty.name());
                     }
-                })
-                .unwrap();
+                    let menu = Paragraph::new(text)
+                        .block(Block::default().title("New puzzle").borders(Borders::ALL));
 
-            let event = events.next().unwrap();
+                    frame.render_widget(menu, terminal_rect);
+                } else if self.mode == Mode::SaveAs {
+                    let prompt = Paragraph::new(format!(
+                        "File name (Enter to save, Esc to cancel):\n {}_",
+                        self.input
+                    ))
+                    .block(Block::default().title("Save as").borders(Borders::ALL));
+
+                    frame.render_widget(prompt, terminal_rect);
+                } else {
+                    self.board_area = terminal_rect;
+                    let board_widget = BoardWidget {
+                        geometry: self.geometry,
+                        theme,
+                    };
+                    frame.render_stateful_widget(
+                        board_widget,
+                        terminal_rect,
+                        self.board.borrow_mut(),
+                    );
+
+                    // last line inside the border, under the board
+                    let status_rect = Rect {
+                        x: terminal_rect.x + 1,
+                        y: terminal_rect.y + terminal_rect.height - 2,
+                        width: terminal_rect.width - 2,
+                        height: 1,
+                    };
+                    frame.render_widget(Paragraph::new(self.status()), status_rect);
+                }
+            })?;
+
+            let event = events.next().map_err(io::Error::other)?;
 
             match event {
-                Event::Input(key) => {
-                    match key {
-                        // movement using arrow keys or vim movement keys
-                        Key::Up | Key::Char('w') => self.board.move_up(),
-                        Key::Down | Key::Char('s') => self.board.move_down(),
-                        Key::Left | Key::Char('a') => self.board.move_left(),
-                        Key::Right | Key::Char('d') => self.board.move_right(),
-                        Key::Char('W') => {
-                            self.board.move_up();
-                            self.board.move_up();
-                            self.board.move_up();
-                        }
-                        Key::Char('S') => {
-                            self.board.move_down();
-                            self.board.move_down();
-                            self.board.move_down();
-                        }
-                        Key::Char('A') => {
-                            self.board.move_left();
-                            self.board.move_left();
-                            self.board.move_left();
-                        }
-                        Key::Char('D') => {
-                            self.board.move_right();
-                            self.board.move_right();
-                            self.board.move_right();
-                        }
-                        Key::Char('i') => self.mode = Mode::Insert,
-                        Key::Char('n') => self.mode = Mode::Note,
-                        Key::Char('m') => self.mode = Mode::Mark,
-                        Key::Char('M') => self.mode = Mode::MarkColorSelect,
-                        Key::Char('h') => self.mode = Mode::HighlightOnly,
-                        Key::Char('H') => self.mode = Mode::Highlight,
-                        Key::Char('f') => {
-                            if self.mode == Mode::Features {
-                                self.mode = Mode::Insert;
-                            } else {
-                                self.mode = Mode::Features;
+                Event::Input(TermEvent::FocusGained) => self.focused = true,
+                Event::Input(TermEvent::FocusLost) => self.focused = false,
+                // anything but quitting resumes
+                Event::Input(TermEvent::Key(key)) if self.paused => {
+                    if self.keymap.action(Key::from(key)) == Some(Action::Quit) {
+                        break;
+                    }
+                    self.paused = false;
+                }
+                Event::Input(TermEvent::Mouse(event)) if !self.paused => self.mouse(event),
+                Event::Input(TermEvent::Key(key)) if self.mode == Mode::SaveAs => {
+                    let key = Key::from(key);
+                    // characters are typed into the name, chords can still quit
+                    if key.is_chord() && self.keymap.action(key) == Some(Action::Quit) {
+                        break;
+                    }
+                    self.save_as_input(key)
+                }
+                Event::Input(TermEvent::Key(_)) if self.help => self.help = false,
+                Event::Input(TermEvent::Key(key)) => {
+                    let key = Key::from(key);
+                    match self.keymap.action(key) {
+                        Some(Action::Quit) => break,
+                        Some(action) => self.perform(action),
+                        None => {
+                            if let KeyCode::Char(char @ '0'..='9') = key.code {
+                                let value = char as usize - '0' as usize;
+                                if let Some((_, _, press)) =
+                                    NUMBERS.iter().find(|(mode, ..)| *mode == self.mode)
+                                {
+                                    press(self, value);
+                                }
                             }
                         }
-                        Key::Char('c') => self.board.reset_colors(),
-                        Key::Ctrl('c') => break,
-                        _ => match self.mode {
-                            Mode::Insert => match key {
-                                Key::Char('1') => self.board.set_value(1),
-                                Key::Char('2') => self.board.set_value(2),
-                                Key::Char('3') => self.board.set_value(3),
-                                Key::Char('4') => self.board.set_value(4),
-                                Key::Char('5') => self.board.set_value(5),
-                                Key::Char('6') => self.board.set_value(6),
-                                Key::Char('7') => self.board.set_value(7),
-                                Key::Char('8') => self.board.set_value(8),
-                                Key::Char('9') => self.board.set_value(9),
-                                Key::Char('0') => self.board.set_value(0),
-                                _ => {}
-                            },
-                            Mode::Note => match key {
-                                Key::Char('1') => self.board.toggle_option(1),
-                                Key::Char('2') => self.board.toggle_option(2),
-                                Key::Char('3') => self.board.toggle_option(3),
-                                Key::Char('4') => self.board.toggle_option(4),
-                                Key::Char('5') => self.board.toggle_option(5),
-                                Key::Char('6') => self.board.toggle_option(6),
-                                Key::Char('7') => self.board.toggle_option(7),
-                                Key::Char('8') => self.board.toggle_option(8),
-                                Key::Char('9') => self.board.toggle_option(9),
-                                _ => {}
-                            },
-                            Mode::Highlight => match key {
-                                Key::Char('1') => self.board.highlight(1),
-                                Key::Char('2') => self.board.highlight(2),
-                                Key::Char('3') => self.board.highlight(3),
-                                Key::Char('4') => self.board.highlight(4),
-                                Key::Char('5') => self.board.highlight(5),
-                                Key::Char('6') => self.board.highlight(6),
-                                Key::Char('7') => self.board.highlight(7),
-                                Key::Char('8') => self.board.highlight(8),
-                                Key::Char('9') => self.board.highlight(9),
-                                _ => {}
-                            },
-                            Mode::HighlightOnly => match key {
-                                Key::Char('1') => self.board.highlight_only(1),
-                                Key::Char('2') => self.board.highlight_only(2),
-                                Key::Char('3') => self.board.highlight_only(3),
-                                Key::Char('4') => self.board.highlight_only(4),
-                                Key::Char('5') => self.board.highlight_only(5),
-                                Key::Char('6') => self.board.highlight_only(6),
-                                Key::Char('7') => self.board.highlight_only(7),
-                                Key::Char('8') => self.board.highlight_only(8),
-                                Key::Char('9') => self.board.highlight_only(9),
-                                _ => {}
-                            },
-                            Mode::Mark => match key {
-                                Key::Char('1') => self.board.mark(1, self.mark_selected_color),
-                                Key::Char('2') => self.board.mark(2, self.mark_selected_color),
-                                Key::Char('3') => self.board.mark(3, self.mark_selected_color),
-                                Key::Char('4') => self.board.mark(4, self.mark_selected_color),
-                                Key::Char('5') => self.board.mark(5, self.mark_selected_color),
-                                Key::Char('6') => self.board.mark(6, self.mark_selected_color),
-                                Key::Char('7') => self.board.mark(7, self.mark_selected_color),
-                                Key::Char('8') => self.board.mark(8, self.mark_selected_color),
-                                Key::Char('9') => self.board.mark(9, self.mark_selected_color),
-                                _ => {}
-                            },
-                            Mode::MarkColorSelect => match key {
-                                Key::Char('1') => {
-                                    self.mark_selected_color = Theme::default().white;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('2') => {
-                                    self.mark_selected_color = Theme::default().black;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('3') => {
-                                    self.mark_selected_color = Theme::default().red;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('4') => {
-                                    self.mark_selected_color = Theme::default().green;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('5') => {
-                                    self.mark_selected_color = Theme::default().yellow;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('6') => {
-                                    self.mark_selected_color = Theme::default().blue;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('7') => {
-                                    self.mark_selected_color = Theme::default().purple
-                                }
-                                Key::Char('8') => {
-                                    self.mark_selected_color = Theme::default().cyan;
-                                    self.mode = Mode::Mark;
-                                }
-                                Key::Char('9') => {
-                                    self.mark_selected_color = Theme::default().grey;
-                                    self.mode = Mode::Mark;
-                                }
-                                _ => {}
-                            },
-                            Mode::Features => match key {
-                                Key::Esc => self.mode = Mode::Insert,
-                                Key::Char('1') => {
-                                    self.board.autofill();
-                                    self.mode = Mode::Insert;
-                                }
-                                Key::Char('3') => {
-                                    save(&self.board.grid);
-                                    self.mode = Mode::Insert;
-                                }
-                                _ => {}
-                            },
-
-                            _ => {}
-                        },
                     }
                 }
-                Event::Tick => (),
+                Event::Tick => {
+                    if self.timing() {
+                        self.ticks += 1;
+                    }
+                    if self.message_ticks > 0 {
+                        self.message_ticks -= 1;
+                        if self.message_ticks == 0 {
+                            self.message.clear();
+                        }
+                    }
+                }
+                // resizing only needs the redraw at the top of the loop
+                Event::Input(_) => (),
             }
         }
-        terminal
+        Ok(())
+    }
+}
+
+// colors picked with `M` and a number, in that order
+fn mark_colors() -> [Slot; 9] {
+    [
+        Slot::White,
+        Slot::Black,
+        Slot::Red,
+        Slot::Green,
+        Slot::Yellow,
+        Slot::Blue,
+        Slot::Purple,
+        Slot::Cyan,
+        Slot::Grey,
+    ]
+}
+
+// `m:ss`, or `h:mm:ss` after an hour
+fn clock(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+fn rating(grid: &Grid) -> String {
+    match solver::solve(grid) {
+        Solution::Unique(_) => rater::rate(grid).describe(),
+        _ => String::new(),
     }
 }
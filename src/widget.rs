@@ -0,0 +1,473 @@
+use crate::{board::Board, theme::Theme};
+use tui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::StatefulWidget,
+};
+
+// How big the board is drawn, picked by the space there is.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Geometry {
+    // 7x3 cells with spaced out options and a dotted cursor around them
+    Full,
+    // 3x3 cells, one character per option, cursor in brackets
+    Compact,
+    // one character per cell, no options
+    Digits,
+}
+
+impl Geometry {
+    // the largest geometry fitting in the area, leaving the last line free
+    pub fn fit(area: Rect) -> Option<Geometry> {
+        [Geometry::Full, Geometry::Compact, Geometry::Digits]
+            .into_iter()
+            .find(|geometry| {
+                let (width, height) = geometry.size();
+                area.width >= width && area.height >= height
+            })
+    }
+
+    // width and height needed, borders and the status line included
+    pub fn size(&self) -> (u16, u16) {
+        match self {
+            Geometry::Full => (80, 41),
+            Geometry::Compact => (40, 32),
+            Geometry::Digits => (23, 14),
+        }
+    }
+
+    // Middle of a cell on screen. Cells have a gap between them and an extra
+    // one between boxes.
+    fn center(&self, area: Rect, row: usize, column: usize) -> (u16, u16) {
+        let row = row as u16;
+        let column = column as u16;
+        match self {
+            Geometry::Full => (
+                column * 8 + 1 + area.x + 3 + column / 3,
+                row * 4 + 1 + area.y + 1 + row / 3,
+            ),
+            Geometry::Compact => (
+                area.x + 2 + column * 4 + column / 3,
+                area.y + 2 + row * 3 + row / 3,
+            ),
+            Geometry::Digits => (
+                area.x + 2 + column * 2 + column / 3,
+                area.y + 1 + row + row / 3,
+            ),
+        }
+    }
+
+    // how far a cell reaches left and right, and up and down of its center
+    fn reach(&self) -> (u16, u16) {
+        match self {
+            Geometry::Full => (3, 1),
+            Geometry::Compact => (1, 1),
+            Geometry::Digits => (0, 0),
+        }
+    }
+}
+
+pub struct BoardWidget<'a> {
+    pub geometry: Geometry,
+    // the model only knows slots, colors come from the theme
+    pub theme: &'a Theme,
+}
+
+impl BoardWidget<'_> {
+    // outline around a cell, dotted or in brackets
+    fn frame(&self, buf: &mut Buffer, area: Rect, position: (usize, usize), color: Color) {
+        let (center_x, center_y) = self.geometry.center(area, position.0, position.1);
+        let (reach_x, reach_y) = self.geometry.reach();
+
+        if self.geometry == Geometry::Full {
+            for x in center_x - 3..=center_x + 3 {
+                buf.get_mut(x, center_y - 2).set_char('.').set_fg(color);
+                buf.get_mut(x, center_y + 2).set_char('.').set_fg(color);
+            }
+        }
+        let (left, right) = match self.geometry {
+            Geometry::Full => ('.', '.'),
+            _ => ('[', ']'),
+        };
+        for y in center_y - reach_y..=center_y + reach_y {
+            buf.get_mut(center_x - reach_x - 1, y)
+                .set_char(left)
+                .set_fg(color);
+            buf.get_mut(center_x + reach_x + 1, y)
+                .set_char(right)
+                .set_fg(color);
+        }
+    }
+
+    // Cell under a screen position, with the option drawn there if any.
+    pub fn hit(
+        area: Rect,
+        geometry: Geometry,
+        x: u16,
+        y: u16,
+    ) -> Option<((usize, usize), Option<usize>)> {
+        let (reach_x, reach_y) = geometry.reach();
+        for row in 0..9 {
+            for column in 0..9 {
+                let (center_x, center_y) = geometry.center(area, row, column);
+                if x + reach_x < center_x
+                    || x > center_x + reach_x
+                    || y + reach_y < center_y
+                    || y > center_y + reach_y
+                {
+                    continue;
+                }
+
+                let dx = x + reach_x - center_x;
+                let dy = y + reach_y - center_y;
+                let option = match geometry {
+                    // options sit on every other column of the cell
+                    Geometry::Full if dx % 2 == 1 => Some((dy * 3 + dx / 2 + 1) as usize),
+                    Geometry::Compact => Some((dy * 3 + dx + 1) as usize),
+                    _ => None,
+                };
+                return Some(((row, column), option));
+            }
+        }
+        None
+    }
+
+    // compact and digit only boards
+    fn render_small(&self, area: Rect, buf: &mut Buffer, state: &Board) {
+        let conflicts = state.grid.conflicts();
+        let theme = self.theme;
+        let (reach_x, reach_y) = self.geometry.reach();
+
+        for row in 0..9 {
+            for column in 0..9 {
+                let cell = state.grid.cell_at(row, column);
+                let (cell_bg, cell_fg) = cell.colors();
+                let (bg, fg) = if conflicts[row][column] {
+                    (theme.orange, theme.black)
+                } else {
+                    (theme.color(cell_bg), theme.color(cell_fg))
+                };
+                let (center_x, center_y) = self.geometry.center(area, row, column);
+
+                for y in center_y - reach_y..=center_y + reach_y {
+                    for x in center_x - reach_x..=center_x + reach_x {
+                        buf.get_mut(x, y).set_char(' ').set_bg(bg).set_fg(fg);
+                    }
+                }
+
+                if cell.value != 0 {
+                    let mut style = Style::default().add_modifier(Modifier::UNDERLINED);
+                    if !cell.initial {
+                        style = style.add_modifier(Modifier::ITALIC);
+                    }
+                    buf.get_mut(center_x, center_y)
+                        .set_char(char::from_digit(cell.value as u32, 10).unwrap_or('e'))
+                        .set_style(style);
+                } else if self.geometry == Geometry::Digits {
+                    buf.get_mut(center_x, center_y).set_char('·');
+                } else {
+                    for (index, option) in cell.options.values.iter().enumerate() {
+                        if !option.valid {
+                            continue;
+                        }
+                        let x = center_x + (index % 3) as u16 - 1;
+                        let y = center_y + (index / 3) as u16 - 1;
+                        let (option_bg, option_fg) = if option.has_default_colors() {
+                            (bg, fg)
+                        } else {
+                            (theme.color(option.bg), theme.color(option.fg))
+                        };
+                        buf.get_mut(x, y)
+                            .set_char(char::from_digit(index as u32 + 1, 10).unwrap_or('e'))
+                            .set_bg(option_bg)
+                            .set_fg(option_fg);
+                    }
+                }
+            }
+        }
+
+        for position in &state.selection {
+            self.frame(buf, area, *position, theme.cyan);
+        }
+        self.frame(buf, area, state.current_position, Color::Reset);
+    }
+}
+
+impl StatefulWidget for BoardWidget<'_> {
+    type State = Board;
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        if self.geometry != Geometry::Full {
+            self.render_small(area, buf, state);
+            return;
+        }
+
+        // render top line
+
+        let mut row: u16 = 0;
+        let mut column: u16 = 0;
+
+        let conflicts = state.grid.conflicts();
+        let theme = self.theme;
+
+        loop {
+            loop {
+                // loop controll
+
+                // println!("areax: {} areay: {} areaw: {} areah: {}, bufx: {} bufy: {} statex: {} statey: {}", area.x, area.y, area.width, area.height, bufx, bufy, statex, statey);
+
+                let cell = state.grid.cell_at(row as usize, column as usize);
+                let (bg, fg) = if conflicts[row as usize][column as usize] {
+                    (theme.orange, theme.black)
+                } else {
+                    let (bg, fg) = cell.colors();
+                    (theme.color(bg), theme.color(fg))
+                };
+                let value = char::from_u32(cell.value as u32 + 48).unwrap_or('e');
+
+                let (center_x, center_y) =
+                    self.geometry.center(area, row as usize, column as usize);
+
+                if cell.value != 0 {
+                    // solved cell
+
+                    if cell.initial {
+                        buf.get_mut(center_x, center_y)
+                            .set_char(value)
+                            .set_bg(bg)
+                            .set_fg(fg)
+                            .set_style(Style::default().add_modifier(Modifier::UNDERLINED));
+                    } else {
+                        buf.get_mut(center_x, center_y)
+                            .set_char(value)
+                            .set_bg(bg)
+                            .set_fg(fg)
+                            .set_style(
+                                Style::default()
+                                    .add_modifier(Modifier::UNDERLINED)
+                                    .add_modifier(Modifier::ITALIC),
+                            );
+                    }
+
+                    // surroundings
+                    buf.get_mut(center_x - 3, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x - 3, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x - 3, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+
+                    buf.get_mut(center_x - 2, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x - 2, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x - 2, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+
+                    buf.get_mut(center_x - 1, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x - 1, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x - 1, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+
+                    buf.get_mut(center_x, center_y - 1).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x, center_y + 1).set_bg(bg).set_fg(fg);
+
+                    buf.get_mut(center_x + 1, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x + 1, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x + 1, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+
+                    buf.get_mut(center_x + 2, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x + 2, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x + 2, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+
+                    buf.get_mut(center_x + 3, center_y - 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                    buf.get_mut(center_x + 3, center_y).set_bg(bg).set_fg(fg);
+                    buf.get_mut(center_x + 3, center_y + 1)
+                        .set_bg(bg)
+                        .set_fg(fg);
+                } else {
+                    // cell with options, uncolored ones take the colors of the cell
+                    let (cell_bg, cell_fg) = cell.colors();
+                    let option_colors: Vec<(Color, Color)> = cell
+                        .options
+                        .values
+                        .iter()
+                        .map(|option| {
+                            if option.has_default_colors() {
+                                (theme.color(cell_bg), theme.color(cell_fg))
+                            } else {
+                                (theme.color(option.bg), theme.color(option.fg))
+                            }
+                        })
+                        .collect();
+                    let (cell_bg, cell_fg) = (theme.color(cell_bg), theme.color(cell_fg));
+                    buf.get_mut(center_x - 3, center_y - 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x - 2, center_y - 1)
+                        .set_char(if cell.options.values[0].valid {
+                            '1'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[0].0)
+                        .set_fg(option_colors[0].1);
+                    buf.get_mut(center_x - 1, center_y - 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x, center_y - 1)
+                        .set_char(if cell.options.values[1].valid {
+                            '2'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[1].0)
+                        .set_fg(option_colors[1].1);
+                    buf.get_mut(center_x + 1, center_y - 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x + 2, center_y - 1)
+                        .set_char(if cell.options.values[2].valid {
+                            '3'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[2].0)
+                        .set_fg(option_colors[2].1);
+                    buf.get_mut(center_x + 3, center_y - 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+
+                    buf.get_mut(center_x - 3, center_y)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x - 2, center_y)
+                        .set_char(if cell.options.values[3].valid {
+                            '4'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[3].0)
+                        .set_fg(option_colors[3].1);
+                    buf.get_mut(center_x - 1, center_y)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x, center_y)
+                        .set_char(if cell.options.values[4].valid {
+                            '5'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[4].0)
+                        .set_fg(option_colors[4].1);
+                    buf.get_mut(center_x + 1, center_y)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x + 2, center_y)
+                        .set_char(if cell.options.values[5].valid {
+                            '6'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[5].0)
+                        .set_fg(option_colors[5].1);
+                    buf.get_mut(center_x + 3, center_y)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+
+                    buf.get_mut(center_x - 3, center_y + 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x - 2, center_y + 1)
+                        .set_char(if cell.options.values[6].valid {
+                            '7'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[6].0)
+                        .set_fg(option_colors[6].1);
+                    buf.get_mut(center_x - 1, center_y + 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x, center_y + 1)
+                        .set_char(if cell.options.values[7].valid {
+                            '8'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[7].0)
+                        .set_fg(option_colors[7].1);
+                    buf.get_mut(center_x + 1, center_y + 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                    buf.get_mut(center_x + 2, center_y + 1)
+                        .set_char(if cell.options.values[8].valid {
+                            '9'
+                        } else {
+                            ' '
+                        })
+                        .set_bg(option_colors[8].0)
+                        .set_fg(option_colors[8].1);
+                    buf.get_mut(center_x + 3, center_y + 1)
+                        .set_bg(cell_bg)
+                        .set_fg(cell_fg);
+                }
+
+                // buf.get_mut(area.x + bufx + 1, area.y + bufy + 1)
+                //     .set_char(value);
+
+                // loop controll
+                column += 1;
+                if column == 9 {
+                    column = 0;
+                    break;
+                }
+            }
+            row += 1;
+            if row == 9 {
+                break;
+            }
+        }
+
+        // render selection, then the cursor over it
+        for (row, column) in &state.selection {
+            self.frame(buf, area, (*row, *column), theme.cyan);
+        }
+        if state.current_position.0 != 9 && state.current_position.1 != 9 {
+            self.frame(buf, area, state.current_position, Color::Reset);
+        }
+    }
+}
+
+// pub struct BoardWidget {
+//     buffer: Buffer,
+// }
+
+// impl BoardWidget {
+//     pub fn from(board: Board) -> Self {
+//         Self {
+//             buffer: Buffer::default(),
+//         }
+//     }
+// }
+
+// impl Widget for BoardWidget {
+//     fn render(self, area: tui::layout::Rect, buf: &mut tui::buffer::Buffer) {
+//         todo!()
+//     }
+// }